@@ -0,0 +1,68 @@
+use bevy::math::UVec2;
+use serde::{Deserialize, Serialize};
+///The duration new frames get, 10 frames per second
+pub const DEFAULT_FRAME_DURATION_MS: u32 = 100;
+///A single frame of an [Animation](Animation), it shows a whole tile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationFrame {
+    ///The frame's tile, like in [TilePosition](super::tile_entity::TilePosition)
+    pub tile_position: UVec2,
+    pub duration_ms: u32,
+}
+///A named sequence of tiles that are shown one after the other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Animation {
+    pub name: String,
+    pub frames: Vec<AnimationFrame>,
+}
+///The animations made from a [TileSetBundle](super::tileset_entity::TileSetBundle)'s tiles
+#[derive(Debug, Default, Clone)]
+pub struct TileSetAnimations {
+    pub animations: Vec<Animation>,
+}
+///Which animation the preview shows and how far it got
+#[derive(Debug, Default)]
+pub struct AnimationPlayback {
+    ///The index of the previewed animation in the selected tileset's [TileSetAnimations](TileSetAnimations)
+    pub animation: Option<usize>,
+    pub frame: usize,
+    ///How long the current frame was shown for
+    pub frame_elapsed_seconds: f32,
+    pub playing: bool,
+}
+impl AnimationPlayback {
+    ///Goes back to the start of the animation
+    pub fn rewind(&mut self) {
+        self.frame = 0;
+        self.frame_elapsed_seconds = 0.0;
+    }
+}
+///Shows the tiles before and after the edited tile under it, to help with drawing frame by frame animations
+///
+///Tiles are ordered like text, from left to right and then from the top row down
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnionSkinSettings {
+    pub enabled: bool,
+    ///How many tiles are shown on each side of the edited tile
+    pub frames: usize,
+    ///The color the tiles before the edited tile are mixed with, it's alpha is how much of it is mixed in
+    pub previous_tint: [u8; 4],
+    ///The color the tiles after the edited tile are mixed with, it's alpha is how much of it is mixed in
+    pub next_tint: [u8; 4],
+    ///The opacity of the closest tiles, the ones further away fade out
+    pub opacity: u8,
+}
+impl Default for OnionSkinSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frames: 1,
+            previous_tint: [255, 64, 64, 128],
+            next_tint: [64, 128, 255, 128],
+            opacity: 128,
+        }
+    }
+}
+///A marker for the sprite that shows the onion skin over the edited tile
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OnionSkinOverlay;
@@ -1,12 +1,19 @@
-use bevy::{prelude::*, reflect::TypeUuid};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
 use serde::Deserialize;
+///A brush, the pixels it paints around the mouse
+///
+///The pixels are stored row by row from the bottom row up, like pixel positions in a tileset
 #[derive(Debug, Deserialize, TypeUuid)]
 #[uuid = "988026cf-8c68-415a-88a5-e515b26c8841"]
 pub struct Pattern {
     pub size: UVec2,
     pub pattern_pixels: Vec<[u8; 4]>,
 }
-//TODO: Create a loader for these sometime
 impl Pattern {
     pub fn new(pattern_pixels: Vec<[u8; 4]>) -> Self {
         Self {
@@ -14,4 +21,52 @@ impl Pattern {
             size: UVec2::new(0, 0),
         }
     }
+    ///Creates a pattern from the pixels of an image, where rows go from the top to the bottom
+    pub fn from_image(image: &image::RgbaImage) -> Self {
+        Self {
+            //Images start at their top row, patterns at their bottom row
+            pattern_pixels: image.rows().rev().flatten().map(|pixel| pixel.0).collect(),
+            size: UVec2::new(image.width(), image.height()),
+        }
+    }
+}
+///Loads [Pattern](Pattern)s from `.pattern` ron files and `.patternpng` images
+///
+///A `.pattern` file looks like `(size: (3, 3), pattern_pixels: [(255, 0, 0, 255), ...])`.
+///Bevy picks a loader by the file's last extension only, so pattern images can't end with `.png`, that would make them textures
+#[derive(Debug, Default)]
+pub struct PatternLoader;
+impl AssetLoader for PatternLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let is_image = load_context
+                .path()
+                .to_string_lossy()
+                .to_lowercase()
+                .ends_with(".patternpng");
+            let pattern = if is_image {
+                Pattern::from_image(&image::load_from_memory(bytes)?.into_rgba8())
+            } else {
+                ron::de::from_bytes::<Pattern>(bytes)?
+            };
+            if pattern.pattern_pixels.len() != (pattern.size.x * pattern.size.y) as usize {
+                anyhow::bail!(
+                    "The pattern is {}x{} but it has {} pixels",
+                    pattern.size.x,
+                    pattern.size.y,
+                    pattern.pattern_pixels.len()
+                );
+            }
+            load_context.set_default_asset(LoadedAsset::new(pattern));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pattern", "patternpng"]
+    }
 }
@@ -0,0 +1,69 @@
+use bevy::{prelude::*, reflect::TypeUuid, render::renderer::RenderResources};
+
+use super::tileset_entity::TileSetSettings;
+///The most pixels a [TileSetChunk](TileSetChunk)'s texture has on each side, bigger tilesets are split into several chunks
+pub const MAX_CHUNK_SIZE_PIXELS: usize = 2048;
+///A part of a [TileSetBundle](super::tileset_entity::TileSetBundle) that's drawn as a single texture
+///
+///The tiles are still entities, but only their chunks are rendered
+#[derive(Debug, Default, Clone)]
+pub struct TileSetChunk {
+    ///The chunk's bottom left tile
+    pub min_tile: UVec2,
+    ///How many tiles the chunk has on each side, the chunks at the top and right of the tileset can be smaller
+    pub size_in_tiles: UVec2,
+}
+impl TileSetChunk {
+    ///Returns where a rectangle of a tile's pixels starts in the chunk's texture, the rectangle's rows start at the top of the tile like in [TileData](super::tile_entity::TileData)
+    pub fn get_texture_origin(
+        &self,
+        tileset_settings: &TileSetSettings,
+        tile_position: UVec2,
+        pixel_in_tile: UVec2,
+    ) -> UVec2 {
+        let tile_in_chunk = tile_position - self.min_tile;
+        //Textures start at the top, but tile positions start at the bottom
+        UVec2::new(
+            tile_in_chunk.x * tileset_settings.tile_width as u32 + pixel_in_tile.x,
+            (self.size_in_tiles.y - 1 - tile_in_chunk.y) * tileset_settings.tile_height as u32
+                + pixel_in_tile.y,
+        )
+    }
+}
+///The [TileSetChunk](TileSetChunk) entities of a [TileSetBundle](super::tileset_entity::TileSetBundle), row by row starting at the bottom left chunk
+#[derive(Debug, Default, Clone)]
+pub struct TileSetChunks {
+    ///How many tiles each chunk has on each side, except the smaller ones at the edges
+    pub chunk_size_in_tiles: UVec2,
+    ///How many chunks are in a row
+    pub columns: u32,
+    pub chunks: Vec<Entity>,
+}
+impl TileSetChunks {
+    ///Returns how many tiles a chunk of this tileset has on each side
+    pub fn get_chunk_size_in_tiles(tileset_settings: &TileSetSettings) -> UVec2 {
+        UVec2::new(
+            (MAX_CHUNK_SIZE_PIXELS / tileset_settings.tile_width)
+                .max(1)
+                .min(tileset_settings.tileset_width) as u32,
+            (MAX_CHUNK_SIZE_PIXELS / tileset_settings.tile_height)
+                .max(1)
+                .min(tileset_settings.tileset_height) as u32,
+        )
+    }
+    ///Returns the chunk a tile is drawn in
+    pub fn get_chunk(&self, tile_position: UVec2) -> Option<Entity> {
+        let chunk_position = tile_position / self.chunk_size_in_tiles;
+        self.chunks
+            .get((chunk_position.y * self.columns + chunk_position.x) as usize)
+            .copied()
+    }
+}
+///The material of a [TileSetChunk](TileSetChunk), it's shader draws a checkerboard under transparent pixels
+///
+///The grid between the tiles is drawn over the chunks by the tileset's [TileSetGrid](super::grid::TileSetGrid)
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "60180a36-e258-4eda-9301-e74c39a71f6b"]
+pub struct TileSetChunkMaterial {
+    pub texture: Handle<Texture>,
+}
@@ -0,0 +1,70 @@
+use bevy::math::UVec2;
+use std::path::PathBuf;
+///Sent by the gui when the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle) should be written to disk as a png sprite sheet
+#[derive(Debug, Clone)]
+pub struct ExportTileSetEvent {
+    pub path: PathBuf,
+}
+///Sent by the gui when some tiles of the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle) should be written as an animated gif or png
+#[derive(Debug, Clone)]
+pub struct ExportAnimationEvent {
+    pub path: PathBuf,
+    ///The frames' tiles in the order they're shown, like in [TilePosition](super::tile_entity::TilePosition)
+    pub tiles: Vec<UVec2>,
+    pub frame_delay_ms: u32,
+}
+///Sent by the gui when a png sprite sheet should be opened as a new [TileSetBundle](super::tileset_entity::TileSetBundle)
+#[derive(Debug, Clone)]
+pub struct OpenImageEvent {
+    pub path: PathBuf,
+    pub tile_width: usize,
+    pub tile_height: usize,
+}
+///Sent by the gui when every open [TileSetBundle](super::tileset_entity::TileSetBundle) should be saved to a project file
+#[derive(Debug, Clone)]
+pub struct SaveProjectEvent {
+    pub path: PathBuf,
+}
+///Sent by the gui when a project file should replace the open [TileSetBundle](super::tileset_entity::TileSetBundle)s
+#[derive(Debug, Clone)]
+pub struct OpenProjectEvent {
+    pub path: PathBuf,
+}
+///Sent by the gui when a palette file should replace the [Palette](super::palette::Palette)
+#[derive(Debug, Clone)]
+pub struct OpenPaletteEvent {
+    pub path: PathBuf,
+}
+///Sent by the gui when the [Palette](super::palette::Palette) should be written to a palette file
+#[derive(Debug, Clone)]
+pub struct SavePaletteEvent {
+    pub path: PathBuf,
+}
+///Sent by the gui to change how the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle) stores it's colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorModeEvent {
+    ///Every pixel is changed to the nearest color of the [Palette](super::palette::Palette)
+    ToIndexed,
+    ToRgba,
+}
+///Sent by the gui when swatches of the [Palette](super::palette::Palette) were removed or moved, so the currently selected indexed [TileSetBundle](super::tileset_entity::TileSetBundle)'s tiles keep their colors
+#[derive(Debug, Clone)]
+pub struct RemapPaletteIndicesEvent {
+    ///The new index of every old index, at the old index
+    pub new_indices: Vec<u8>,
+}
+///Sent by the gui to change the layers of the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerEvent {
+    ///Adds an empty layer on top of the others
+    Add,
+    Remove(usize),
+    ///Makes a layer the one the tools draw on
+    Select(usize),
+}
+///Sent to move through the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle)'s [TileSetHistory](super::history::TileSetHistory)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+}
@@ -0,0 +1,67 @@
+use bevy::{prelude::*, reflect::TypeUuid, render::renderer::RenderResources};
+///The grids drawn over the tilesets, set in the Options menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridSettings {
+    ///Draws the borders between the tiles
+    pub show_tile_grid: bool,
+    ///Draws the borders between the pixels when zoomed in enough
+    pub show_pixel_grid: bool,
+    pub tile_grid_color: [u8; 4],
+    pub pixel_grid_color: [u8; 4],
+    ///How many pixels on the screen a tileset's pixel has to cover before the pixel grid shows
+    pub pixel_grid_min_zoom: f32,
+}
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            show_tile_grid: true,
+            show_pixel_grid: true,
+            tile_grid_color: [0, 0, 0, 255],
+            pixel_grid_color: [0, 0, 0, 64],
+            pixel_grid_min_zoom: 8.0,
+        }
+    }
+}
+impl GridSettings {
+    ///Returns the color the tile grid is drawn with, it's transparent while the grid is hidden
+    pub fn get_tile_grid_color(&self) -> Color {
+        get_grid_color(self.show_tile_grid, self.tile_grid_color)
+    }
+    ///Returns the color the pixel grid is drawn with, it's transparent while the grid is hidden
+    pub fn get_pixel_grid_color(&self) -> Color {
+        get_grid_color(self.show_pixel_grid, self.pixel_grid_color)
+    }
+}
+fn get_grid_color(shown: bool, [r, g, b, a]: [u8; 4]) -> Color {
+    if shown {
+        Color::rgba_u8(r, g, b, a)
+    } else {
+        Color::rgba_u8(0, 0, 0, 0)
+    }
+}
+///A marker for the mesh over a [TileSetBundle](super::tileset_entity::TileSetBundle) that shows it's grids
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TileSetGrid;
+///The material of a [TileSetGrid](TileSetGrid), it's shader draws lines that keep their width on the screen at every zoom
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "b5f5e7c2-6a0e-4c4f-9d3a-2f8e1c7d9a41"]
+pub struct TileSetGridMaterial {
+    ///The size of a tile in pixels, so the shader knows where the borders are
+    pub tile_size: Vec2,
+    ///The size of the whole tileset in pixels
+    pub tileset_size: Vec2,
+    pub tile_grid_color: Color,
+    pub pixel_grid_color: Color,
+    pub pixel_grid_min_zoom: f32,
+}
+impl TileSetGridMaterial {
+    pub fn new(tile_size: Vec2, tileset_size: Vec2, grid_settings: &GridSettings) -> Self {
+        Self {
+            tile_size,
+            tileset_size,
+            tile_grid_color: grid_settings.get_tile_grid_color(),
+            pixel_grid_color: grid_settings.get_pixel_grid_color(),
+            pixel_grid_min_zoom: grid_settings.pixel_grid_min_zoom,
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::tile_entity::{TileData, TileSettings};
+///How many strokes a [TileSetHistory](TileSetHistory) remembers before forgetting the oldest ones
+pub const MAX_HISTORY_LENGTH: usize = 256;
+///The strokes done on a [TileSetBundle](super::tileset_entity::TileSetBundle), so they can be undone and redone
+#[derive(Debug, Default)]
+pub struct TileSetHistory {
+    pub undo_stack: Vec<HistoryEntry>,
+    pub redo_stack: Vec<HistoryEntry>,
+}
+impl TileSetHistory {
+    ///Adds a new stroke, the strokes that were undone before it can't be redone anymore
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.redo_stack.clear();
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > MAX_HISTORY_LENGTH {
+            self.undo_stack.remove(0);
+        }
+    }
+    ///Forgets the changes made on a removed layer, the changes on the layers above it move down with their layers
+    ///
+    ///Strokes that only changed the removed layer are forgotten completely
+    pub fn remove_layer(&mut self, layer: usize) {
+        for stack in [&mut self.undo_stack, &mut self.redo_stack].iter_mut() {
+            for entry in stack.iter_mut() {
+                entry.changes.retain(|change| change.layer != layer);
+                for change in entry.changes.iter_mut() {
+                    if change.layer > layer {
+                        change.layer -= 1;
+                    }
+                }
+            }
+            stack.retain(|entry| !entry.changes.is_empty());
+        }
+    }
+}
+///A single stroke, from pressing a mouse button until releasing it
+#[derive(Debug)]
+pub struct HistoryEntry {
+    pub name: String,
+    pub changes: Vec<TileChange>,
+}
+///The pixels a stroke changed in a single tile, only the rectangle that actually changed is kept
+#[derive(Debug)]
+pub struct TileChange {
+    pub tile_entity: Entity,
+    ///The layer the stroke was drawn on
+    pub layer: usize,
+    pub region: TileRegion,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+}
+impl TileChange {
+    ///Compares a tile's data before and after a stroke, returns None if nothing changed
+    pub fn new(
+        tile_entity: Entity,
+        layer: usize,
+        tile_settings: &TileSettings,
+        before: &[u8],
+        after: &[u8],
+    ) -> Option<Self> {
+        //Blank tiles have no pixels, they're compared as transparent ones
+        let blank_pixels;
+        let (before, after) = match (before.is_empty(), after.is_empty()) {
+            (true, false) => {
+                blank_pixels = vec![0; after.len()];
+                (&blank_pixels[..], after)
+            }
+            (false, true) => {
+                blank_pixels = vec![0; before.len()];
+                (before, &blank_pixels[..])
+            }
+            _ => (before, after),
+        };
+        if before.len() != after.len() {
+            return None;
+        }
+        let mut changed: Option<(usize, usize, usize, usize)> = None;
+        for (pixel_index, (before_pixel, after_pixel)) in before
+            .chunks_exact(4)
+            .zip(after.chunks_exact(4))
+            .enumerate()
+        {
+            if before_pixel != after_pixel {
+                let row = pixel_index / tile_settings.tile_width;
+                let column = pixel_index % tile_settings.tile_width;
+                changed = Some(match changed {
+                    Some((first_row, last_row, first_column, last_column)) => (
+                        first_row.min(row),
+                        last_row.max(row),
+                        first_column.min(column),
+                        last_column.max(column),
+                    ),
+                    None => (row, row, column, column),
+                });
+            }
+        }
+        let (first_row, last_row, first_column, last_column) = changed?;
+        let region = TileRegion {
+            first_row,
+            first_column,
+            width: last_column - first_column + 1,
+            height: last_row - first_row + 1,
+            tile_width: tile_settings.tile_width,
+        };
+        Some(Self {
+            tile_entity,
+            layer,
+            before: region.copy_from(before),
+            after: region.copy_from(after),
+            region,
+        })
+    }
+    ///Puts the pixels from before the stroke back in the tile's layer
+    pub fn undo(&self, layer_data: &mut [u8]) {
+        self.region.paste_into(&self.before, layer_data);
+    }
+    ///Puts the pixels from after the stroke back in the tile's layer
+    pub fn redo(&self, layer_data: &mut [u8]) {
+        self.region.paste_into(&self.after, layer_data);
+    }
+}
+///A rectangle of pixels inside a tile's [TileData](TileData), rows are counted from the top like in the data
+#[derive(Debug, Clone, Copy)]
+pub struct TileRegion {
+    pub first_row: usize,
+    pub first_column: usize,
+    pub width: usize,
+    pub height: usize,
+    pub tile_width: usize,
+}
+impl TileRegion {
+    fn row_ranges(&self) -> impl Iterator<Item = std::ops::Range<usize>> {
+        let region = *self;
+        (region.first_row..region.first_row + region.height).map(move |row| {
+            let start = (row * region.tile_width + region.first_column) * 4;
+            start..start + region.width * 4
+        })
+    }
+    ///Copies this region's pixels out of a tile's data
+    pub fn copy_from(&self, data: &[u8]) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for range in self.row_ranges() {
+            pixels.extend_from_slice(&data[range]);
+        }
+        pixels
+    }
+    ///Writes pixels copied with [copy_from](TileRegion::copy_from) back into a tile's data
+    pub fn paste_into(&self, pixels: &[u8], data: &mut [u8]) {
+        for (range, row) in self.row_ranges().zip(pixels.chunks_exact(self.width * 4)) {
+            data[range].copy_from_slice(row);
+        }
+    }
+}
+///The tiles the stroke that's in progress changed, and how they looked before it started
+#[derive(Debug, Default)]
+pub struct CurrentStroke {
+    pub tiles_before: HashMap<Entity, Vec<u8>>,
+}
+impl CurrentStroke {
+    ///Tools call this before changing a tile's data, only the first call in a stroke keeps the data
+    pub fn record(&mut self, tile_entity: Entity, tile_data: &TileData) {
+        self.tiles_before
+            .entry(tile_entity)
+            .or_insert_with(|| tile_data.data.clone());
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change_on_layer(layer: usize) -> TileChange {
+        TileChange::new(
+            Entity::new(0),
+            layer,
+            &TileSettings {
+                tile_width: 1,
+                tile_height: 1,
+            },
+            &[0, 0, 0, 0],
+            &[1, 2, 3, 4],
+        )
+        .unwrap()
+    }
+    fn entry_on_layers(layers: &[usize]) -> HistoryEntry {
+        HistoryEntry {
+            name: "Stroke".to_string(),
+            changes: layers.iter().map(|layer| change_on_layer(*layer)).collect(),
+        }
+    }
+    fn layers_of(stack: &[HistoryEntry]) -> Vec<Vec<usize>> {
+        stack
+            .iter()
+            .map(|entry| entry.changes.iter().map(|change| change.layer).collect())
+            .collect()
+    }
+    const TEST_TILE: TileSettings = TileSettings {
+        tile_width: 4,
+        tile_height: 3,
+    };
+    ///A tile's data where every pixel has it's own color
+    fn numbered_pixels() -> Vec<u8> {
+        (0..TEST_TILE.tile_width * TEST_TILE.tile_height)
+            .flat_map(|pixel| vec![pixel as u8, 0, 0, 255])
+            .collect()
+    }
+    fn with_pixel(mut data: Vec<u8>, row: usize, column: usize, color: [u8; 4]) -> Vec<u8> {
+        let index = (row * TEST_TILE.tile_width + column) * 4;
+        data[index..index + 4].copy_from_slice(&color);
+        data
+    }
+    ///Checks that undoing gives back the data from before and redoing the data from after
+    fn assert_round_trip(before: &[u8], after: &[u8]) -> TileChange {
+        let change = TileChange::new(Entity::new(0), 0, &TEST_TILE, before, after).unwrap();
+        let blank_pixels = vec![0; TEST_TILE.tile_width * TEST_TILE.tile_height * 4];
+        let mut data = if after.is_empty() {
+            blank_pixels.clone()
+        } else {
+            after.to_vec()
+        };
+        change.undo(&mut data);
+        assert_eq!(
+            data,
+            if before.is_empty() {
+                &blank_pixels[..]
+            } else {
+                before
+            }
+        );
+        change.redo(&mut data);
+        assert_eq!(
+            data,
+            if after.is_empty() {
+                &blank_pixels[..]
+            } else {
+                after
+            }
+        );
+        change
+    }
+    #[test]
+    fn unchanged_tiles_have_no_change() {
+        let data = numbered_pixels();
+        assert!(TileChange::new(Entity::new(0), 0, &TEST_TILE, &data, &data).is_none());
+        assert!(TileChange::new(Entity::new(0), 0, &TEST_TILE, &[], &[]).is_none());
+    }
+    #[test]
+    fn a_single_pixel_change_keeps_a_single_pixel() {
+        let before = numbered_pixels();
+        let after = with_pixel(before.clone(), 1, 2, [9, 9, 9, 9]);
+        let change = assert_round_trip(&before, &after);
+        assert_eq!(
+            (
+                change.region.first_row,
+                change.region.first_column,
+                change.region.width,
+                change.region.height
+            ),
+            (1, 2, 1, 1)
+        );
+        assert_eq!(change.before, vec![6, 0, 0, 255]);
+        assert_eq!(change.after, vec![9, 9, 9, 9]);
+    }
+    #[test]
+    fn changes_in_opposite_corners_keep_the_whole_tile() {
+        let before = numbered_pixels();
+        let after = with_pixel(before.clone(), 0, 0, [9; 4]);
+        let after = with_pixel(
+            after,
+            TEST_TILE.tile_height - 1,
+            TEST_TILE.tile_width - 1,
+            [9; 4],
+        );
+        let change = assert_round_trip(&before, &after);
+        assert_eq!(
+            (change.region.width, change.region.height),
+            (TEST_TILE.tile_width, TEST_TILE.tile_height)
+        );
+    }
+    #[test]
+    fn changes_on_the_edges_keep_only_their_rows_and_columns() {
+        let before = numbered_pixels();
+        //The right column
+        let after = with_pixel(before.clone(), 0, 3, [9; 4]);
+        let after = with_pixel(after, 2, 3, [9; 4]);
+        let change = assert_round_trip(&before, &after);
+        assert_eq!(
+            (
+                change.region.first_row,
+                change.region.first_column,
+                change.region.width,
+                change.region.height
+            ),
+            (0, 3, 1, 3)
+        );
+        //The bottom row
+        let after = with_pixel(before.clone(), 2, 0, [9; 4]);
+        let after = with_pixel(after, 2, 3, [9; 4]);
+        let change = assert_round_trip(&before, &after);
+        assert_eq!(
+            (
+                change.region.first_row,
+                change.region.first_column,
+                change.region.width,
+                change.region.height
+            ),
+            (2, 0, 4, 1)
+        );
+    }
+    #[test]
+    fn blank_tiles_are_compared_as_transparent() {
+        let blank_pixels = vec![0; TEST_TILE.tile_width * TEST_TILE.tile_height * 4];
+        let drawn = with_pixel(blank_pixels, 2, 1, [9; 4]);
+        let change = assert_round_trip(&[], &drawn);
+        assert_eq!(change.before, vec![0; 4]);
+        let change = assert_round_trip(&drawn, &[]);
+        assert_eq!(change.after, vec![0; 4]);
+    }
+    #[test]
+    fn removing_a_layer_keeps_the_other_layers_history() {
+        let mut history = TileSetHistory::default();
+        history.push(entry_on_layers(&[0]));
+        history.push(entry_on_layers(&[1]));
+        history.push(entry_on_layers(&[0, 1, 2]));
+        history.push(entry_on_layers(&[2]));
+        history.redo_stack.push(entry_on_layers(&[1, 2]));
+        history.remove_layer(1);
+        assert_eq!(
+            layers_of(&history.undo_stack),
+            vec![vec![0], vec![0, 1], vec![1]]
+        );
+        assert_eq!(layers_of(&history.redo_stack), vec![vec![1]]);
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use super::tile_entity::{TileData, TileIndices};
+///How a layer's pixels are mixed with the layers under it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Add,
+    Overlay,
+}
+impl BlendMode {
+    pub const ALL: [BlendMode; 4] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Add,
+        BlendMode::Overlay,
+    ];
+    ///The blend mode's name, as shown in the gui
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Add => "Add",
+            BlendMode::Overlay => "Overlay",
+        }
+    }
+    ///Blends a color channel of the layer with the one under it, both from 0 to 1
+    fn blend_channel(&self, below: f32, above: f32) -> f32 {
+        match self {
+            BlendMode::Normal => above,
+            BlendMode::Multiply => below * above,
+            BlendMode::Add => f32::min(below + above, 1.0),
+            BlendMode::Overlay => {
+                if below <= 0.5 {
+                    2.0 * below * above
+                } else {
+                    1.0 - 2.0 * (1.0 - below) * (1.0 - above)
+                }
+            }
+        }
+    }
+}
+///The settings of a single layer of a [TileSetBundle](super::tileset_entity::TileSetBundle), each tile has it's own pixels for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerSettings {
+    pub name: String,
+    pub visible: bool,
+    ///Locked layers can't be drawn on
+    pub locked: bool,
+    ///Multiplies the alpha of the layer's pixels
+    pub opacity: u8,
+    pub blend_mode: BlendMode,
+}
+impl LayerSettings {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            visible: true,
+            locked: false,
+            opacity: u8::MAX,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+///The layers of a [TileSetBundle](super::tileset_entity::TileSetBundle), the first one is at the bottom
+#[derive(Debug, Clone)]
+pub struct TileSetLayers {
+    pub layers: Vec<LayerSettings>,
+    ///The layer the tools draw on, it's pixels are in the tiles' [TileData](TileData)
+    pub active_layer: usize,
+}
+impl Default for TileSetLayers {
+    fn default() -> Self {
+        Self {
+            layers: vec![LayerSettings::new("Layer 1".to_string())],
+            active_layer: 0,
+        }
+    }
+}
+impl TileSetLayers {
+    ///Checks whether the tools can draw on the active layer, hidden layers can't be drawn on since the changes can't be seen
+    pub fn is_active_layer_editable(&self) -> bool {
+        self.layers
+            .get(self.active_layer)
+            .map_or(false, |layer| layer.visible && !layer.locked)
+    }
+}
+///The pixels of a tile in a single layer
+#[derive(Debug, Default, Clone)]
+pub struct LayerPixels {
+    ///RGBA8 pixels like in [TileData](TileData), empty when the layer is blank in this tile
+    pub data: Vec<u8>,
+    ///The palette indices of the pixels when the tileset is indexed, like in [TileIndices](TileIndices)
+    pub indices: Vec<u8>,
+}
+///The pixels of every layer of a tile, in the same order as the [TileSetLayers](TileSetLayers)
+///
+///The active layer's pixels are moved to the tile's [TileData](TileData) so the tools can draw on them, it's entry here is left empty
+#[derive(Debug, Clone)]
+pub struct TileLayers {
+    pub layers: Vec<LayerPixels>,
+}
+impl Default for TileLayers {
+    fn default() -> Self {
+        Self {
+            layers: vec![LayerPixels::default()],
+        }
+    }
+}
+impl TileLayers {
+    ///Returns the RGBA8 pixels of a layer, empty if the layer is blank in this tile
+    pub fn layer_data<'a>(
+        &'a self,
+        layer: usize,
+        tileset_layers: &TileSetLayers,
+        tile_data: &'a TileData,
+    ) -> &'a [u8] {
+        if layer == tileset_layers.active_layer {
+            &tile_data.data
+        } else {
+            self.layers
+                .get(layer)
+                .map_or(&[][..], |layer_pixels| layer_pixels.data.as_slice())
+        }
+    }
+    ///Returns the palette indices of a layer, like [layer_data](TileLayers::layer_data) does for it's pixels
+    pub fn layer_indices<'a>(
+        &'a self,
+        layer: usize,
+        tileset_layers: &TileSetLayers,
+        tile_indices: &'a TileIndices,
+    ) -> &'a [u8] {
+        if layer == tileset_layers.active_layer {
+            &tile_indices.indices
+        } else {
+            self.layers
+                .get(layer)
+                .map_or(&[][..], |layer_pixels| layer_pixels.indices.as_slice())
+        }
+    }
+    ///Blends the visible layers of a tile together into the pixels that are shown, empty if every layer is blank
+    pub fn composite(&self, tileset_layers: &TileSetLayers, tile_data: &TileData) -> Vec<u8> {
+        //A single visible normal layer at full opacity looks exactly like it's pixels
+        if let [layer] = tileset_layers.layers.as_slice() {
+            if layer.visible && layer.opacity == u8::MAX && layer.blend_mode == BlendMode::Normal {
+                return tile_data.data.clone();
+            }
+        }
+        //Blank layers have no pixels, the composite gets the size of the ones that do
+        let composite_length = (0..tileset_layers.layers.len())
+            .map(|layer_index| {
+                self.layer_data(layer_index, tileset_layers, tile_data)
+                    .len()
+            })
+            .max()
+            .unwrap_or(0);
+        let mut composite = vec![0; composite_length];
+        for (layer_index, layer) in tileset_layers.layers.iter().enumerate() {
+            let data = self.layer_data(layer_index, tileset_layers, tile_data);
+            if !layer.visible || data.len() != composite.len() {
+                continue;
+            }
+            for (below, above) in composite.chunks_exact_mut(4).zip(data.chunks_exact(4)) {
+                let blended = blend_pixels(
+                    [below[0], below[1], below[2], below[3]],
+                    [above[0], above[1], above[2], above[3]],
+                    layer.blend_mode,
+                    layer.opacity,
+                );
+                below.copy_from_slice(&blended);
+            }
+        }
+        composite
+    }
+}
+///Puts a layer's pixel over the pixel made by the layers under it
+pub fn blend_pixels(below: [u8; 4], above: [u8; 4], blend_mode: BlendMode, opacity: u8) -> [u8; 4] {
+    let to_float = |channel: u8| channel as f32 / 255.0;
+    let above_alpha = to_float(above[3]) * to_float(opacity);
+    if above_alpha == 0.0 {
+        return below;
+    }
+    let below_alpha = to_float(below[3]);
+    let alpha = above_alpha + below_alpha * (1.0 - above_alpha);
+    let mut pixel = [0; 4];
+    for channel in 0..3 {
+        let below_channel = to_float(below[channel]);
+        let above_channel = to_float(above[channel]);
+        //Where there's nothing under the layer, the blend mode has nothing to blend with
+        let blended = (1.0 - below_alpha) * above_channel
+            + below_alpha * blend_mode.blend_channel(below_channel, above_channel);
+        let channel_value =
+            (above_alpha * blended + below_alpha * below_channel * (1.0 - above_alpha)) / alpha;
+        pixel[channel] = (channel_value * 255.0).round() as u8;
+    }
+    pixel[3] = (alpha * 255.0).round() as u8;
+    pixel
+}
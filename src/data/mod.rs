@@ -1,5 +1,14 @@
+pub mod animations;
 pub mod assets;
+pub mod chunks;
+pub mod events;
+pub mod grid;
+pub mod history;
+pub mod layers;
+pub mod palette;
+pub mod project;
 pub mod resources;
+pub mod selection;
 pub mod shared_components;
 pub mod tile_entity;
 pub mod tileset_entity;
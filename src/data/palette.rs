@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+///The index indexed tiles use for transparent pixels, so palettes can have up to 255 colors in them
+pub const TRANSPARENT_INDEX: u8 = u8::MAX;
+///A color in the [Palette](Palette) with an optional name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Swatch {
+    pub color: [u8; 4],
+    pub name: String,
+}
+///The colors of the game being worked on, shown as swatches next to the tileset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    pub swatches: Vec<Swatch>,
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            name: "Untitled".to_string(),
+            swatches: vec![
+                Swatch {
+                    color: [0, 0, 0, 255],
+                    name: "Black".to_string(),
+                },
+                Swatch {
+                    color: [255, 255, 255, 255],
+                    name: "White".to_string(),
+                },
+            ],
+        }
+    }
+}
+impl Palette {
+    ///Returns the index of the swatch closest to a color, half transparent colors and empty palettes give the [TRANSPARENT_INDEX](TRANSPARENT_INDEX)
+    pub fn nearest_index(&self, color: [u8; 4]) -> u8 {
+        if color[3] < 128 {
+            return TRANSPARENT_INDEX;
+        }
+        let distance = |swatch: &Swatch| {
+            (0..3)
+                .map(|channel| {
+                    let difference = swatch.color[channel] as i32 - color[channel] as i32;
+                    difference * difference
+                })
+                .sum::<i32>()
+        };
+        self.swatches
+            .iter()
+            .take(TRANSPARENT_INDEX as usize)
+            .enumerate()
+            .min_by_key(|(_, swatch)| distance(swatch))
+            .map(|(index, _)| index as u8)
+            .unwrap_or(TRANSPARENT_INDEX)
+    }
+    ///Removes a swatch and returns the new index of every old index, the removed swatch's index goes to the nearest color that's left
+    pub fn remove_swatch(&mut self, index: usize) -> Vec<u8> {
+        let removed = self.swatches.remove(index);
+        let nearest_index = self.nearest_index(removed.color);
+        (0..=u8::MAX)
+            .map(|old_index| match old_index as usize {
+                _ if old_index == TRANSPARENT_INDEX => TRANSPARENT_INDEX,
+                old if old == index => nearest_index,
+                old if old > index && old <= self.swatches.len() => old_index - 1,
+                _ => old_index,
+            })
+            .collect()
+    }
+    ///Swaps two swatches and returns the new index of every old index
+    pub fn swap_swatches(&mut self, a: usize, b: usize) -> Vec<u8> {
+        self.swatches.swap(a, b);
+        (0..=u8::MAX)
+            .map(|old_index| match old_index as usize {
+                _ if old_index == TRANSPARENT_INDEX => TRANSPARENT_INDEX,
+                old if old == a => b as u8,
+                old if old == b => a as u8,
+                _ => old_index,
+            })
+            .collect()
+    }
+    ///Returns the color of a swatch, indices without a swatch are transparent
+    pub fn indexed_color(&self, index: u8) -> [u8; 4] {
+        match self.swatches.get(index as usize) {
+            Some(swatch) if index != TRANSPARENT_INDEX => swatch.color,
+            _ => [0; 4],
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    animations::Animation, layers::LayerSettings, palette::Palette, tileset_entity::TileSetSettings,
+};
+///The native project file, it holds everything needed to reopen every tileset exactly how it was saved
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectDocument {
+    pub tilesets: Vec<TileSetDocument>,
+    ///The index of the tileset that was selected when the project was saved
+    pub selected_tileset: Option<usize>,
+}
+///A single [TileSetBundle](super::tileset_entity::TileSetBundle) in a [ProjectDocument](ProjectDocument)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TileSetDocument {
+    pub name: String,
+    pub settings: TileSetSettings,
+    pub view: TileSetViewDocument,
+    ///Row by row starting at the bottom left tile
+    pub tiles: Vec<TileDocument>,
+    ///The palette of indexed tilesets, their tiles' indices are saved in the tiles
+    #[serde(default)]
+    pub palette: Option<Palette>,
+    ///From the bottom layer up, projects saved before tilesets had layers have a single layer
+    #[serde(default)]
+    pub layers: Vec<LayerSettings>,
+    #[serde(default)]
+    pub animations: Vec<Animation>,
+}
+///The camera part of a [TileSetView](super::tileset_entity::TileSetView)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TileSetViewDocument {
+    pub camera_translation: [f32; 3],
+    pub camera_scale: [f32; 3],
+}
+///A single [TileBundle](super::tile_entity::TileBundle) in a [TileSetDocument](TileSetDocument)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TileDocument {
+    pub name: String,
+    pub position: [u32; 2],
+    ///The bottom layer's RGBA8 pixels, rows from top to bottom, like [TileData](super::tile_entity::TileData), empty if it's blank
+    pub data: Vec<u8>,
+    ///The pixels of the layers above the bottom one, in the same format
+    #[serde(default)]
+    pub layers: Vec<Vec<u8>>,
+    ///The bottom layer's palette indices if the tileset is indexed, like [TileIndices](super::tile_entity::TileIndices)
+    ///
+    ///They're saved instead of found again from the colors, a palette can have the same color more than once
+    #[serde(default)]
+    pub indices: Vec<u8>,
+    ///The palette indices of the layers above the bottom one
+    #[serde(default)]
+    pub layer_indices: Vec<Vec<u8>>,
+}
@@ -1,9 +1,65 @@
-use bevy::{math::UVec2, prelude::Handle};
+use std::path::PathBuf;
 
-use super::assets::Pattern;
+use bevy::{
+    math::UVec2,
+    prelude::{Assets, Handle, MouseButton, Texture},
+    render::texture::{Extent3d, TextureDimension, TextureFormat},
+    utils::HashMap,
+};
+
+use super::{assets::Pattern, chunks::TileSetChunkMaterial, tileset_entity::TileSetOrder};
 pub enum SelectedTool {
     Pan,
-    Pencil { pattern_handle: Handle<Pattern> },
+    Pencil {
+        pattern_handle: Handle<Pattern>,
+    },
+    ///Erases the pixels under the pattern's non transparent pixels
+    Eraser {
+        pattern_handle: Handle<Pattern>,
+    },
+    ///Fills the clicked area with the drawing color
+    Fill,
+    ///Draws a line between where the mouse was pressed and released
+    Line,
+    ///Draws a rectangle between where the mouse was pressed and released
+    Rectangle,
+    ///Draws an ellipse inside the rectangle between where the mouse was pressed and released
+    Ellipse,
+    ///Makes the clicked pixel's color the drawing color
+    Picker,
+    ///Selects a rectangle of pixels that can be moved, copied, cut and pasted
+    Select,
+    ///Adds the pixels inside a freehand shape to the selection mask
+    Lasso,
+    ///Adds the pixels with the clicked pixel's color to the selection mask
+    MagicWand,
+}
+impl SelectedTool {
+    ///The tool's name, as shown in the gui
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectedTool::Pan => "Pan",
+            SelectedTool::Pencil { .. } => "Pencil",
+            SelectedTool::Eraser { .. } => "Eraser",
+            SelectedTool::Fill => "Fill",
+            SelectedTool::Line => "Line",
+            SelectedTool::Rectangle => "Rectangle",
+            SelectedTool::Ellipse => "Ellipse",
+            SelectedTool::Picker => "Picker",
+            SelectedTool::Select => "Select",
+            SelectedTool::Lasso => "Lasso",
+            SelectedTool::MagicWand => "Magic Wand",
+        }
+    }
+    ///The pattern used by the brush tools
+    pub fn pattern_handle(&self) -> Option<&Handle<Pattern>> {
+        match self {
+            SelectedTool::Pencil { pattern_handle } | SelectedTool::Eraser { pattern_handle } => {
+                Some(pattern_handle)
+            }
+            _ => None,
+        }
+    }
 }
 pub struct MousePixelPosition {
     pub pixel_position: Option<UVec2>,
@@ -15,3 +71,159 @@ impl Default for MousePixelPosition {
         }
     }
 }
+///The project file the open tilesets were last saved to or opened from
+#[derive(Debug, Default)]
+pub struct ProjectPath {
+    pub path: Option<PathBuf>,
+}
+///Hands out the [TileSetOrder](super::tileset_entity::TileSetOrder) of every tileset that's opened
+#[derive(Debug, Default)]
+pub struct TileSetOrderCounter {
+    next_order: u64,
+}
+impl TileSetOrderCounter {
+    pub fn next(&mut self) -> TileSetOrder {
+        let order = TileSetOrder {
+            order: self.next_order,
+        };
+        self.next_order += 1;
+        order
+    }
+}
+///The settings shared by the brush tools
+#[derive(Debug)]
+pub struct BrushSettings {
+    ///Skips the pixels that make L shaped corners in a stroke, so lines stay one pixel thin
+    pub pixel_perfect: bool,
+    ///How much alpha the eraser removes from a pixel with each stamp, 255 makes it fully transparent at once
+    pub eraser_strength: u8,
+    ///Whether the brush paints the pattern's shape with the drawing color or stamps the pattern's own colors
+    pub mode: BrushMode,
+}
+impl Default for BrushSettings {
+    fn default() -> Self {
+        Self {
+            pixel_perfect: false,
+            eraser_strength: u8::MAX,
+            mode: BrushMode::Mask,
+        }
+    }
+}
+///The colors the tools draw with, the left mouse button uses the primary color and the right one uses the secondary color
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawingColors {
+    pub primary: [u8; 4],
+    pub secondary: [u8; 4],
+}
+impl Default for DrawingColors {
+    fn default() -> Self {
+        Self {
+            primary: [0, 0, 0, 255],
+            secondary: [255, 255, 255, 255],
+        }
+    }
+}
+impl DrawingColors {
+    ///The color a mouse button draws with
+    pub fn get(&self, button: MouseButton) -> [u8; 4] {
+        match button {
+            MouseButton::Right => self.secondary,
+            _ => self.primary,
+        }
+    }
+    pub fn get_mut(&mut self, button: MouseButton) -> &mut [u8; 4] {
+        match button {
+            MouseButton::Right => &mut self.secondary,
+            _ => &mut self.primary,
+        }
+    }
+}
+///How the brushes use their pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushMode {
+    ///The pattern's non transparent pixels are painted with the drawing color
+    Mask,
+    ///The pattern's pixels are copied with their own colors
+    Stamp,
+}
+///Which pixels the fill tool fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillMode {
+    ///The connected pixels with the same color, without leaving the clicked tile
+    Tile,
+    ///The connected pixels with the same color, across tile borders
+    Contiguous,
+    ///Every pixel in the tileset with the same color
+    Global,
+}
+///The settings of the fill tool
+#[derive(Debug)]
+pub struct FillSettings {
+    pub mode: FillMode,
+    ///How much each channel of a pixel can differ from the clicked pixel's color and still get filled
+    pub tolerance: u8,
+}
+impl Default for FillSettings {
+    fn default() -> Self {
+        Self {
+            mode: FillMode::Contiguous,
+            tolerance: 0,
+        }
+    }
+}
+///The settings of the shape tools
+#[derive(Debug, Default)]
+pub struct ShapeSettings {
+    ///Rectangles and ellipses are drawn filled instead of only their outline
+    pub filled: bool,
+}
+///A changed rectangle of a chunk's texture, waiting to be copied to the gpu
+#[derive(Debug)]
+pub struct TextureUpload {
+    pub texture_handle: Handle<Texture>,
+    ///The rectangle's top left pixel in the texture
+    pub origin: UVec2,
+    pub size: UVec2,
+    ///The rectangle's RGBA8 pixels, row by row from the top
+    pub pixels: Vec<u8>,
+}
+///The texture rectangles that changed this frame, the upload node in the render graph takes them
+#[derive(Debug, Default)]
+pub struct TextureUploads {
+    pub uploads: Vec<TextureUpload>,
+}
+///The material every blank [TileSetChunk](super::chunks::TileSetChunk) of some size shares, so chunks nothing was drawn in don't need their own texture
+#[derive(Debug, Default)]
+pub struct BlankChunkMaterials {
+    ///By the chunk's size in pixels
+    materials: HashMap<(u32, u32), Handle<TileSetChunkMaterial>>,
+}
+impl BlankChunkMaterials {
+    ///Returns the blank material of chunks with this size, creating it the first time chunks like these need it
+    ///
+    ///The texture has the chunk's size so blank chunks show the same checkerboard as transparent pixels
+    pub fn get_or_add(
+        &mut self,
+        chunk_size: UVec2,
+        textures: &mut Assets<Texture>,
+        materials: &mut Assets<TileSetChunkMaterial>,
+    ) -> Handle<TileSetChunkMaterial> {
+        self.materials
+            .entry((chunk_size.x, chunk_size.y))
+            .or_insert_with(|| {
+                materials.add(TileSetChunkMaterial {
+                    texture: textures.add(get_transparent_texture(chunk_size)),
+                })
+            })
+            .clone()
+    }
+}
+///Returns a transparent RGBA8 texture
+pub fn get_transparent_texture(size: UVec2) -> Texture {
+    Texture::new(
+        Extent3d::new(size.x, size.y, 1),
+        TextureDimension::D2,
+        vec![0; (size.x * size.y * 4) as usize],
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
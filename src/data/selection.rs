@@ -0,0 +1,184 @@
+use bevy::{
+    math::{IVec2, UVec2},
+    reflect::TypeUuid,
+    render::renderer::RenderResources,
+};
+///A rectangle of tileset pixels, the max is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+impl PixelRect {
+    ///The rectangle that has both pixels in it, in any order
+    pub fn from_corners(first_pixel: IVec2, second_pixel: IVec2) -> Self {
+        Self {
+            min: first_pixel.min(second_pixel),
+            max: first_pixel.max(second_pixel) + IVec2::ONE,
+        }
+    }
+    pub fn size(&self) -> IVec2 {
+        self.max - self.min
+    }
+    pub fn contains(&self, pixel: IVec2) -> bool {
+        pixel.x >= self.min.x
+            && pixel.y >= self.min.y
+            && pixel.x < self.max.x
+            && pixel.y < self.max.y
+    }
+    ///Moves the rectangle by some pixels, it keeps it's size
+    pub fn translated(&self, offset: IVec2) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+}
+///Pixels that float over the tileset until they're committed, after being lifted out of the tiles or pasted
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingPixels {
+    ///RGBA8 pixels, rows from top to bottom like in [TileData](super::tile_entity::TileData)
+    pub pixels: Vec<u8>,
+    pub size: UVec2,
+}
+impl FloatingPixels {
+    ///Returns where a pixel starts in the pixels, pixel positions start at the bottom left corner like in the tileset
+    pub fn pixel_index(&self, pixel: UVec2) -> usize {
+        ((self.size.y - 1 - pixel.y) * self.size.x + pixel.x) as usize * 4
+    }
+    pub fn get_pixel(&self, pixel: UVec2) -> [u8; 4] {
+        let index = self.pixel_index(pixel);
+        let mut color = [0; 4];
+        color.copy_from_slice(&self.pixels[index..index + 4]);
+        color
+    }
+}
+///The marquee selection on the currently selected [TileSetBundle](super::tileset_entity::TileSetBundle)
+///
+///Ctrl+C, Ctrl+X and Delete work on the selected pixels of the active layer
+#[derive(Debug, Default)]
+pub struct Selection {
+    pub rect: Option<PixelRect>,
+    ///When the selection is moved or pasted it's pixels float in it's rect, Enter puts them in the tiles
+    pub floating: Option<FloatingPixels>,
+}
+///The pixels copied with Ctrl+C or cut with Ctrl+X
+#[derive(Debug, Default)]
+pub struct Clipboard {
+    pub pixels: Option<FloatingPixels>,
+    ///Where the pixels were copied from, they're pasted back at the same place
+    pub position: IVec2,
+}
+///How a new lasso or magic wand selection is combined with the [SelectionMask](SelectionMask) that's already there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskOperation {
+    Replace,
+    Add,
+    Subtract,
+    Intersect,
+}
+impl MaskOperation {
+    pub const ALL: [MaskOperation; 4] = [
+        MaskOperation::Replace,
+        MaskOperation::Add,
+        MaskOperation::Subtract,
+        MaskOperation::Intersect,
+    ];
+    ///The operation's name, as shown in the gui
+    pub fn name(&self) -> &'static str {
+        match self {
+            MaskOperation::Replace => "Replace",
+            MaskOperation::Add => "Add",
+            MaskOperation::Subtract => "Subtract",
+            MaskOperation::Intersect => "Intersect",
+        }
+    }
+}
+///The settings of the lasso and the magic wand
+#[derive(Debug)]
+pub struct MaskSettings {
+    ///Used when no modifier key is held, Shift adds, Alt subtracts and both intersect
+    pub operation: MaskOperation,
+    ///The magic wand only selects the pixels connected to the clicked pixel
+    pub wand_contiguous: bool,
+    ///How much each channel of a pixel can differ from the clicked pixel's color and still get selected
+    pub wand_tolerance: u8,
+}
+impl Default for MaskSettings {
+    fn default() -> Self {
+        Self {
+            operation: MaskOperation::Replace,
+            wand_contiguous: true,
+            wand_tolerance: 0,
+        }
+    }
+}
+///The pixels of a [TileSetBundle](super::tileset_entity::TileSetBundle) selected with the lasso or the magic wand, the brushes only paint inside them
+#[derive(Debug, Default, Clone)]
+pub struct SelectionMask {
+    ///A value per tileset pixel, rows from the top of the tileset like in an image.
+    ///None when nothing is selected, then every pixel can be painted
+    pub mask: Option<Vec<bool>>,
+    ///The tileset's size in pixels
+    pub size: UVec2,
+}
+impl SelectionMask {
+    ///Checks whether the brushes can paint a tileset pixel
+    pub fn can_paint(&self, pixel: IVec2) -> bool {
+        match &self.mask {
+            Some(mask) => {
+                pixel.x >= 0
+                    && pixel.y >= 0
+                    && pixel.x < self.size.x as i32
+                    && pixel.y < self.size.y as i32
+                    && mask[((self.size.y as i32 - 1 - pixel.y) * self.size.x as i32 + pixel.x)
+                        as usize]
+            }
+            None => true,
+        }
+    }
+    ///Combines a new selection with the mask, the new selection has the same layout as the mask
+    pub fn combine(&mut self, selected: Vec<bool>, size: UVec2, operation: MaskOperation) {
+        let mask = match (operation, self.mask.take()) {
+            (MaskOperation::Replace, _) | (MaskOperation::Add, None) => selected,
+            (MaskOperation::Add, Some(mut mask)) => {
+                mask.iter_mut()
+                    .zip(selected.iter())
+                    .for_each(|(masked, selected)| *masked |= *selected);
+                mask
+            }
+            (MaskOperation::Subtract, Some(mut mask)) => {
+                mask.iter_mut()
+                    .zip(selected.iter())
+                    .for_each(|(masked, selected)| *masked &= !*selected);
+                mask
+            }
+            (MaskOperation::Intersect, Some(mut mask)) => {
+                mask.iter_mut()
+                    .zip(selected.iter())
+                    .for_each(|(masked, selected)| *masked &= *selected);
+                mask
+            }
+            //There's nothing to subtract from or intersect with
+            (MaskOperation::Subtract, None) | (MaskOperation::Intersect, None) => return,
+        };
+        //A mask without selected pixels would stop the brushes from painting anywhere
+        self.mask = if mask.contains(&true) {
+            Some(mask)
+        } else {
+            None
+        };
+        self.size = size;
+    }
+}
+///The material of a selection's outline, it's shader colors the dashes so moving them only changes the dash offset
+#[derive(Debug, Clone, PartialEq, RenderResources, TypeUuid)]
+#[uuid = "3c1e8f4a-7b2d-4e6f-a9c0-5d8b2e4f1a73"]
+pub struct MarchingAntsMaterial {
+    ///The outline's width in tileset pixels, it changes with the zoom so the outline keeps it's width on the screen
+    pub line_width: f32,
+    ///How many pixels the dashes moved along the outline
+    pub dash_offset: f32,
+    ///How many pixels each black or white dash is
+    pub dash_length: f32,
+}
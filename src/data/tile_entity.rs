@@ -1,34 +1,34 @@
 use bevy::prelude::*;
-use bevy::render::pipeline::RenderPipeline;
 
-use super::shared_components::Uninitiated;
-//A visual representation of a single tile/sprite
+use super::{layers::TileLayers, shared_components::Uninitiated};
+//A single tile, it's drawn by it's tileset's [TileSetChunk](super::chunks::TileSetChunk) so it only holds the tile's data
 #[derive(Bundle, Clone)]
 pub struct TileBundle {
-    #[bundle]
-    pub sprite: SpriteBundle, //This is the visualizing part, not the data, there's a transaction that changes the texture of the handle in the sprite when the data changes
+    ///The tile's place in the world, for the things drawn over it
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
     pub tile_settings: TileSettings,
     pub data: TileData,
     pub uninitiated: Uninitiated,
     pub name: TileName,
     pub rect: TileRect,
     pub tile_position: TilePosition,
+    pub layers: TileLayers,
+    pub texture: TileTexture,
 }
 impl Default for TileBundle {
     fn default() -> Self {
         Self {
-            sprite: SpriteBundle {
-                render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
-                    crate::CUSTOM_SPRITE_PIPELINE_HANDLE.typed(),
-                )]),
-                ..Default::default()
-            },
+            transform: Transform::default(),
+            global_transform: GlobalTransform::default(),
             data: TileData::default(),
             tile_settings: TileSettings::default(),
             uninitiated: Uninitiated::default(),
             name: TileName::default(),
             rect: TileRect::default(),
             tile_position: TilePosition::default(),
+            layers: TileLayers::default(),
+            texture: TileTexture::default(),
         }
     }
 }
@@ -37,26 +37,126 @@ impl TileBundle {
     pub fn new(
         tile_settings: TileSettings,
         tile_position: TilePosition,
-        material_handle: Handle<ColorMaterial>,
         transform: Transform,
     ) -> Self {
         let mut default = Self::default();
         default.tile_settings = tile_settings;
-        default.sprite.material = material_handle;
-        default.sprite.transform = transform;
+        default.transform = transform;
         default.tile_position = tile_position;
         default
     }
 }
-//TODO: Optimization: make data an option, initiate the tile when the pencil touches it and directly insert the changes on the first draw, before touch make the a single handle for all untouched tiles
+///The tile's RGBA8 pixels, row by row from the top row down like in a texture
+///
+///Blank tiles have no pixels, they're transparent and allocated when something is drawn on them for the first time
 #[derive(Debug, Default, Clone)]
 pub struct TileData {
     pub data: Vec<u8>,
 }
+impl TileData {
+    ///Whether nothing was ever drawn on the tile, so it has no pixels
+    pub fn is_blank(&self) -> bool {
+        self.data.is_empty()
+    }
+    ///Returns where a pixel starts in the data, pixel positions start at the tile's bottom left corner like in the tileset
+    pub fn pixel_index(tile_settings: &TileSettings, pixel_in_tile: UVec2) -> usize {
+        ((tile_settings.tile_height - 1 - pixel_in_tile.y as usize) * tile_settings.tile_width
+            + pixel_in_tile.x as usize)
+            * 4
+    }
+    pub fn get_pixel(&self, tile_settings: &TileSettings, pixel_in_tile: UVec2) -> [u8; 4] {
+        if self.is_blank() {
+            return [0; 4];
+        }
+        let index = Self::pixel_index(tile_settings, pixel_in_tile);
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.data[index..index + 4]);
+        pixel
+    }
+    pub fn set_pixel(
+        &mut self,
+        tile_settings: &TileSettings,
+        pixel_in_tile: UVec2,
+        color: [u8; 4],
+    ) {
+        if self.is_blank() {
+            //Blank tiles are already transparent
+            if color == [0; 4] {
+                return;
+            }
+            self.data = vec![0; tile_settings.tile_width * tile_settings.tile_height * 4];
+        }
+        let index = Self::pixel_index(tile_settings, pixel_in_tile);
+        self.data[index..index + 4].copy_from_slice(&color);
+    }
+}
+///What the tile's pixels in it's [TileSetChunk](super::chunks::TileSetChunk)'s texture on the gpu need
+///
+///The tools mark the pixels they change, so only that rectangle of the texture gets uploaded.
+///A tile whose [TileData](TileData) changed without marking anything is uploaded whole
+#[derive(Debug, Default, Clone)]
+pub struct TileTexture {
+    ///The pixels changed since the last upload, in pixels starting at the tile's top left like in the texture, the max is exclusive
+    pub dirty_rect: Option<(UVec2, UVec2)>,
+    ///Whether the texture shows some of the tile's pixels, blank tiles that show nothing have nothing to upload
+    pub is_shown: bool,
+}
+impl TileTexture {
+    ///Marks a rectangle of pixels as changed, pixel positions start at the tile's bottom left corner like in the [TileData](TileData), the max is exclusive
+    pub fn mark_dirty_rect(&mut self, tile_settings: &TileSettings, min: UVec2, max: UVec2) {
+        //The texture's rows start at the top
+        let tile_height = tile_settings.tile_height as u32;
+        let (min, max) = (
+            UVec2::new(min.x, tile_height - max.y),
+            UVec2::new(max.x, tile_height - min.y),
+        );
+        self.dirty_rect = Some(match self.dirty_rect {
+            Some((dirty_min, dirty_max)) => (dirty_min.min(min), dirty_max.max(max)),
+            None => (min, max),
+        });
+    }
+    ///Marks a single pixel as changed
+    pub fn mark_dirty(&mut self, tile_settings: &TileSettings, pixel_in_tile: UVec2) {
+        self.mark_dirty_rect(tile_settings, pixel_in_tile, pixel_in_tile + UVec2::ONE);
+    }
+    ///Returns the rectangle to upload and forgets it, it's the whole tile if nothing was marked
+    pub fn take_dirty_rect(&mut self, tile_settings: &TileSettings) -> (UVec2, UVec2) {
+        self.dirty_rect.take().unwrap_or((
+            UVec2::ZERO,
+            UVec2::new(
+                tile_settings.tile_width as u32,
+                tile_settings.tile_height as u32,
+            ),
+        ))
+    }
+}
+///Frees pixels that are all transparent, so the tile or layer they belong to is blank
+pub fn free_transparent_pixels(data: &mut Vec<u8>) {
+    if data.iter().all(|channel| *channel == 0) {
+        *data = Vec::new();
+    }
+}
+///The palette indices of a tile in an indexed [TileSetBundle](super::tileset_entity::TileSetBundle), in the same order as the [TileData](TileData)'s pixels
+///
+///The [TileData](TileData) keeps the indices' colors so the tools and the rendering don't need to know about indices
+#[derive(Debug, Default, Clone)]
+pub struct TileIndices {
+    pub indices: Vec<u8>,
+}
+///Where the tile is in it's tileset, counted in tiles from the bottom left tile
 #[derive(Debug, Default, Clone)]
 pub struct TilePosition {
     pub position: UVec2,
 }
+impl TilePosition {
+    ///The tileset pixel position of the tile's bottom left pixel
+    pub fn min_pixel(&self, tile_settings: &TileSettings) -> UVec2 {
+        UVec2::new(
+            self.position.x * tile_settings.tile_width as u32,
+            self.position.y * tile_settings.tile_height as u32,
+        )
+    }
+}
 #[derive(Debug, Default, Clone, Copy)]
 pub struct TileSettings {
     pub tile_width: usize,
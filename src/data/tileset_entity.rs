@@ -1,6 +1,15 @@
-use super::shared_components::Uninitiated;
+use super::{
+    animations::TileSetAnimations,
+    history::TileSetHistory,
+    layers::{TileLayers, TileSetLayers},
+    palette::Palette,
+    selection::SelectionMask,
+    shared_components::Uninitiated,
+    tile_entity::{TileData, TileIndices, TileName},
+};
 use bevy::{prelude::*, utils::HashMap};
 use bevy_common::input::data_components::CameraZoomLimit;
+use serde::{Deserialize, Serialize};
 #[derive(Debug, Default)]
 pub struct OpenDocumentsMap {
     pub document_map: HashMap<usize, TileSetSettings>,
@@ -16,20 +25,29 @@ pub struct TileSetBundle {
     tileset_view: TileSetView,
     tileset_name: TileSetName,
     newly_selected: NewlySelected,
+    history: TileSetHistory,
+    layers: TileSetLayers,
+    animations: TileSetAnimations,
+    selection_mask: SelectionMask,
+    order: TileSetOrder,
 }
 impl TileSetBundle {
-    pub fn new(tileset_settings: TileSetSettings, index: u32) -> Self {
+    pub fn new(tileset_settings: TileSetSettings, index: u32, order: TileSetOrder) -> Self {
         let mut name = "Tileset ".to_string();
         name.push_str(&u32::to_string(&index));
+        Self::with_name(tileset_settings, name, order)
+    }
+    pub fn with_name(tileset_settings: TileSetSettings, name: String, order: TileSetOrder) -> Self {
         Self {
             tileset_settings,
             tileset_name: TileSetName { name },
+            order,
             ..Default::default()
         }
     }
 }
 ///The basic info of the [TileSetBundle](TileSetBundle)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TileSetSettings {
     pub tile_width: usize,
     pub tile_height: usize,
@@ -81,7 +99,101 @@ impl Default for TileSetView {
 pub struct TileSetName {
     pub name: String,
 }
+///When a [TileSetBundle](TileSetBundle) was opened compared to the other ones, the tabs and project files keep the tilesets in this order
+///
+///Unlike entity ids it's never reused, take new ones from the [TileSetOrderCounter](super::resources::TileSetOrderCounter)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TileSetOrder {
+    pub order: u64,
+}
 
 ///This is a marker to help us know which [TileSetBundle](TileSetBundle) is currently viewed
 #[derive(Debug, Default)]
 pub struct NewlySelected;
+
+///The data a [TileSetBundle](TileSetBundle)'s tiles start with instead of being transparent, it's removed once the tiles are spawned
+#[derive(Debug, Default)]
+pub struct LoadedTiles {
+    ///One per tile, row by row starting at the bottom left tile, like the [TilePosition](super::tile_entity::TilePosition)s
+    pub tiles: Vec<LoadedTile>,
+}
+///The data of a single tile in [LoadedTiles](LoadedTiles)
+#[derive(Debug, Default)]
+pub struct LoadedTile {
+    pub name: TileName,
+    ///The pixels of the first layer
+    pub data: TileData,
+    pub layers: TileLayers,
+    ///The first layer's indices if the tileset is indexed, tiles without them are indexed from their colors
+    pub indices: Option<TileIndices>,
+}
+///Makes a [TileSetBundle](TileSetBundle) indexed, it's tiles can only use this palette's colors
+#[derive(Debug, Clone)]
+pub struct TileSetPalette {
+    pub palette: Palette,
+}
+///The tile entities of a [TileSetBundle](TileSetBundle), row by row starting at the bottom left tile like the [TilePosition](super::tile_entity::TilePosition)s
+///
+///Tools look up the tiles they change by their position instead of going over every tile
+#[derive(Debug, Default, Clone)]
+pub struct TileSetTiles {
+    pub tiles: Vec<Entity>,
+}
+impl TileSetTiles {
+    ///Returns the tile at a position, None if it's outside of the tileset
+    pub fn get(&self, tileset_settings: &TileSetSettings, tile_position: IVec2) -> Option<Entity> {
+        if tile_position.x < 0
+            || tile_position.y < 0
+            || tile_position.x as usize >= tileset_settings.tileset_width
+            || tile_position.y as usize >= tileset_settings.tileset_height
+        {
+            return None;
+        }
+        self.tiles
+            .get(
+                tile_position.y as usize * tileset_settings.tileset_width
+                    + tile_position.x as usize,
+            )
+            .copied()
+    }
+    ///Returns the tile a tileset pixel is in, None if it's outside of the tileset
+    pub fn get_at_pixel(&self, tileset_settings: &TileSetSettings, pixel: IVec2) -> Option<Entity> {
+        self.get(
+            tileset_settings,
+            IVec2::new(
+                pixel.x.div_euclid(tileset_settings.tile_width as i32),
+                pixel.y.div_euclid(tileset_settings.tile_height as i32),
+            ),
+        )
+    }
+    ///Returns the tiles that have some of the tileset pixels from min to max, the max is exclusive
+    pub fn get_overlapped(
+        &self,
+        tileset_settings: &TileSetSettings,
+        min_pixel: IVec2,
+        max_pixel: IVec2,
+    ) -> Vec<Entity> {
+        let tile_size = IVec2::new(
+            tileset_settings.tile_width as i32,
+            tileset_settings.tile_height as i32,
+        );
+        //Pixels left of or below the tileset are negative, so the division has to round down
+        let min_tile = IVec2::new(
+            min_pixel.x.div_euclid(tile_size.x),
+            min_pixel.y.div_euclid(tile_size.y),
+        );
+        let max_tile = IVec2::new(
+            (max_pixel.x - 1).div_euclid(tile_size.x),
+            (max_pixel.y - 1).div_euclid(tile_size.y),
+        );
+        let mut overlapped = Vec::new();
+        for y in min_tile.y..=max_tile.y {
+            for x in min_tile.x..=max_tile.x {
+                if let Some(tile) = self.get(tileset_settings, IVec2::new(x, y)) {
+                    overlapped.push(tile);
+                }
+            }
+        }
+        overlapped
+    }
+}
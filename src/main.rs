@@ -2,7 +2,8 @@ use bevy::{
     ecs::component::ComponentDescriptor,
     render::{
         pipeline::PipelineDescriptor,
-        shader::{Shader, ShaderStage},
+        render_graph::{base, AssetRenderResourcesNode, RenderGraph},
+        shader::{Shader, ShaderStage, ShaderStages},
     },
 };
 use bevy::{ecs::component::StorageType, prelude::*, reflect::TypeUuid};
@@ -20,15 +21,32 @@ mod data;
 mod systems;
 mod ui;
 use data::{
-    assets::Pattern,
-    resources::MousePixelPosition,
+    animations::{AnimationPlayback, OnionSkinSettings},
+    assets::{Pattern, PatternLoader},
+    chunks::TileSetChunkMaterial,
+    events::{
+        ColorModeEvent, ExportAnimationEvent, ExportTileSetEvent, HistoryEvent, LayerEvent,
+        OpenImageEvent, OpenPaletteEvent, OpenProjectEvent, RemapPaletteIndicesEvent,
+        SavePaletteEvent, SaveProjectEvent,
+    },
+    grid::{GridSettings, TileSetGridMaterial},
+    history::CurrentStroke,
+    palette::Palette,
+    resources::{
+        BlankChunkMaterials, BrushSettings, DrawingColors, FillSettings, MousePixelPosition,
+        ProjectPath, ShapeSettings, TextureUploads, TileSetOrderCounter,
+    },
+    selection::{Clipboard, MarchingAntsMaterial, MaskSettings, Selection},
     shared_components::{CurrentlySelected, Uninitiated},
     tile_entity::TileBundle,
     tileset_entity::{NewlySelected, TileSetBundle},
 };
 use systems::{
+    animating::*,
+    file_handling::{exporting::*, importing::*, palette::*, project::*},
     initializing::*,
-    tileset_editing::{tools::*, updating_data::*},
+    texture_uploading::*,
+    tileset_editing::{history::*, indexed_colors::*, layers::*, tools::*, updating_data::*},
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -48,7 +66,16 @@ enum SystemLabels {
     GetMousePosPixel,
     TrackMiddleMouseDragging,
     DrawSomething,
+    HistoryShortcuts,
+    CommitStroke,
+    ApplyHistory,
+    ApplyLayerEvents,
+    SyncPalette,
+    RemapPaletteIndices,
+    UpdateIndexedColors,
     UpdateTexturesForVisual,
+    PutDownSelection,
+    CommitSelectionStroke,
 }
 #[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
 enum StageLabels {
@@ -59,8 +86,32 @@ enum StageLabels {
     ///Initialize the newly created [TileBundle](TileBundle)
     InitializeTiles,
     UpdateTiles,
+    ///Handles the file operations requested by the gui, after this frame's edits were applied
+    HandleFiles,
 }
 fn main() {
+    //Animations can be exported from a project file without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("export-animation") {
+        if let Err(error) = run_export_animation_command(&args[1..]) {
+            eprintln!("Couldn't export the animation: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+    //The brush can be benchmarked on big tilesets without opening a window, only in builds with the bench feature
+    #[cfg(feature = "bench")]
+    if args.first().map(String::as_str) == Some("benchmark-brush") {
+        if let Err(error) =
+            systems::tileset_editing::tools::brush_benchmark::run_brush_benchmark_command(
+                &args[1..],
+            )
+        {
+            eprintln!("Couldn't run the benchmark: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
     AppBuilder::default()
         //Turning on deps
         .add_plugins(DefaultPlugins)
@@ -82,17 +133,45 @@ fn main() {
             StorageType::SparseSet,
         ))
         .add_asset::<Pattern>()
+        .init_asset_loader::<PatternLoader>()
         .insert_resource(MouseWorldPosition::default())
         .insert_resource(MousePixelPosition::default())
+        .insert_resource(BrushSettings::default())
+        .insert_resource(FillSettings::default())
+        .insert_resource(DrawingColors::default())
+        .insert_resource(ShapeSettings::default())
         .add_startup_system(spawn_cameras_system.system())
-        .add_startup_system(setup_tile_pipeline.system())
+        .add_startup_system(setup_tileset_pipelines.system())
+        //Chunk textures only upload the pixels that changed
+        .insert_resource(TextureUploads::default())
+        //Tilesets are drawn in chunks, the chunks nothing was drawn in share a material
+        .add_asset::<TileSetChunkMaterial>()
+        .insert_resource(BlankChunkMaterials::default())
+        //The tile grid and the pixel grid are drawn over the chunks
+        .add_asset::<TileSetGridMaterial>()
+        //The selections' outlines move their dashes in their shader
+        .add_asset::<MarchingAntsMaterial>()
+        .insert_resource(GridSettings::default())
+        .add_startup_system(setup_texture_upload_node.system())
         .add_startup_system(setup_tools.system())
+        .add_startup_system(watch_for_asset_changes.system())
         //We always need our gui to be drawn
         .add_system(
             ui::bevy_egui::draw_gui
                 .system()
                 .label(SystemLabels::DrawGui),
         )
+        .add_system(ui::tools_window::draw_tools_window.system())
+        .add_system(ui::colors_window::draw_colors_window.system())
+        .add_system(ui::palette_window::draw_palette_window.system())
+        .add_system(ui::history_window::draw_history_window.system())
+        .add_system(ui::layers_window::draw_layers_window.system())
+        //Animations are previewed while the tileset is edited
+        .insert_resource(AnimationPlayback::default())
+        .insert_resource(OnionSkinSettings::default())
+        .add_system(advance_animation_playback.system())
+        .add_system(ui::timeline_window::draw_timeline_window.system())
+        .add_system(ui::animation_preview_window::draw_animation_preview_window.system())
         //Here we initiallize our newly created tileset
         .add_stage_after(
             CoreStage::Update,
@@ -111,11 +190,40 @@ fn main() {
         .add_stage_after(
             StageLabels::InitializeTiles,
             StageLabels::UpdateView,
-            SystemStage::single_threaded().with_system(update_selected_tileset.system()),
+            SystemStage::single_threaded()
+                //The selection's floating pixels go in the tileset they were made on before it's unselected
+                .with_system(
+                    put_down_selection_before_switching_tilesets
+                        .system()
+                        .label(SystemLabels::PutDownSelection),
+                )
+                .with_system(
+                    commit_stroke
+                        .system()
+                        .label(SystemLabels::CommitSelectionStroke)
+                        .after(SystemLabels::PutDownSelection),
+                )
+                .with_system(
+                    update_selected_tileset
+                        .system()
+                        .after(SystemLabels::CommitSelectionStroke),
+                ),
         )
         //This is the stage where we can actually use the app
         //We need a mouse world position resource for this
         .add_event::<MouseDragEvent>()
+        //Every stroke is remembered so it can be undone
+        .add_event::<HistoryEvent>()
+        .insert_resource(CurrentStroke::default())
+        //Pixels can be selected, moved and copied between tiles
+        .insert_resource(Selection::default())
+        .insert_resource(Clipboard::default())
+        .insert_resource(MaskSettings::default())
+        //Every tileset has layers that are blended together
+        .add_event::<LayerEvent>()
+        //Indexed tilesets keep their tiles in their palette's colors
+        .add_event::<ColorModeEvent>()
+        .add_event::<RemapPaletteIndicesEvent>()
         .add_stage_after(
             StageLabels::UpdateView,
             StageLabels::UpdateTiles,
@@ -155,38 +263,233 @@ fn main() {
                         .label(SystemLabels::DrawSomething)
                         .after(SystemLabels::GetMousePosPixel),
                 )
+                .with_system(
+                    fill_tool
+                        .system()
+                        .label(SystemLabels::DrawSomething)
+                        .after(SystemLabels::GetMousePosPixel),
+                )
+                .with_system(
+                    shape_tool
+                        .system()
+                        .label(SystemLabels::DrawSomething)
+                        .after(SystemLabels::GetMousePosPixel),
+                )
+                .with_system(picker_tool.system().after(SystemLabels::GetMousePosPixel))
+                .with_system(
+                    select_tool
+                        .system()
+                        .label(SystemLabels::DrawSomething)
+                        .after(SystemLabels::GetMousePosPixel),
+                )
+                .with_system(
+                    selection_keyboard_shortcuts
+                        .system()
+                        .label(SystemLabels::DrawSomething),
+                )
+                .with_system(show_selection.system().after(SystemLabels::DrawSomething))
+                //The lasso and the magic wand decide which pixels the brush can paint
+                .with_system(
+                    lasso_tool
+                        .system()
+                        .label(SystemLabels::DrawSomething)
+                        .after(SystemLabels::GetMousePosPixel),
+                )
+                .with_system(
+                    magic_wand_tool
+                        .system()
+                        .label(SystemLabels::DrawSomething)
+                        .after(SystemLabels::GetMousePosPixel),
+                )
+                .with_system(
+                    show_selection_mask
+                        .system()
+                        .after(SystemLabels::DrawSomething),
+                )
+                .with_system(
+                    history_keyboard_shortcuts
+                        .system()
+                        .label(SystemLabels::HistoryShortcuts),
+                )
+                .with_system(
+                    commit_stroke
+                        .system()
+                        .label(SystemLabels::CommitStroke)
+                        .after(SystemLabels::DrawSomething),
+                )
+                .with_system(
+                    apply_history_events
+                        .system()
+                        .label(SystemLabels::ApplyHistory)
+                        .after(SystemLabels::HistoryShortcuts)
+                        .after(SystemLabels::CommitStroke),
+                )
+                .with_system(
+                    apply_layer_events
+                        .system()
+                        .label(SystemLabels::ApplyLayerEvents)
+                        .after(SystemLabels::CommitStroke),
+                )
+                .with_system(change_selected_tileset_color_mode.system())
+                .with_system(
+                    sync_selected_tileset_palette
+                        .system()
+                        .label(SystemLabels::SyncPalette),
+                )
+                .with_system(
+                    index_tiles
+                        .system()
+                        .label(SystemLabels::UpdateIndexedColors),
+                )
+                .with_system(
+                    quantize_changed_tiles
+                        .system()
+                        .label(SystemLabels::UpdateIndexedColors)
+                        .after(SystemLabels::DrawSomething)
+                        .after(SystemLabels::ApplyHistory)
+                        .after(SystemLabels::ApplyLayerEvents),
+                )
+                .with_system(
+                    remap_tile_indices
+                        .system()
+                        .label(SystemLabels::RemapPaletteIndices),
+                )
+                .with_system(
+                    recolor_tiles_with_changed_palette
+                        .system()
+                        .label(SystemLabels::UpdateIndexedColors)
+                        .after(SystemLabels::SyncPalette)
+                        .after(SystemLabels::RemapPaletteIndices),
+                )
+                .with_system(
+                    update_onion_skin
+                        .system()
+                        .after(SystemLabels::DrawSomething)
+                        .after(SystemLabels::ApplyHistory)
+                        .after(SystemLabels::ApplyLayerEvents)
+                        .after(SystemLabels::UpdateIndexedColors),
+                )
                 .with_system(
                     update_textures_for_changed_tile_data
                         .system()
                         .label(SystemLabels::UpdateTexturesForVisual)
-                        .after(SystemLabels::DrawSomething),
+                        .after(SystemLabels::DrawSomething)
+                        .after(SystemLabels::ApplyHistory)
+                        .after(SystemLabels::ApplyLayerEvents)
+                        .after(SystemLabels::UpdateIndexedColors),
                 )
+                .with_system(update_grid_materials.system())
                 .with_system(move_camera_with_wasd_scaled_by_zoom.system())
                 .with_system(zoom_in_camera_with_mouse_scroll.system()),
         )
+        //Reading and writing files, the gui sends events for these
+        .add_event::<ExportTileSetEvent>()
+        .add_event::<ExportAnimationEvent>()
+        .add_event::<OpenImageEvent>()
+        .add_event::<SaveProjectEvent>()
+        .add_event::<OpenProjectEvent>()
+        .add_event::<OpenPaletteEvent>()
+        .add_event::<SavePaletteEvent>()
+        .insert_resource(ProjectPath::default())
+        .insert_resource(TileSetOrderCounter::default())
+        .insert_resource(Palette::default())
+        .add_stage_after(
+            StageLabels::UpdateTiles,
+            StageLabels::HandleFiles,
+            SystemStage::single_threaded()
+                .with_system(export_selected_tileset.system())
+                .with_system(export_selected_tileset_animation.system())
+                .with_system(open_image_as_tileset.system())
+                .with_system(save_project.system())
+                .with_system(open_project.system())
+                .with_system(open_palette.system())
+                .with_system(save_palette.system()),
+        )
         .run();
 }
-pub const CUSTOM_SPRITE_PIPELINE_HANDLE: HandleUntyped =
-    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 2785347850338765446);
+pub const TILESET_CHUNK_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 1918303318280349784);
+pub const TILESET_GRID_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 7344215830918602117);
+pub const MARCHING_ANTS_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 5170264938821457303);
+///The name of the node that sends the [TileSetChunkMaterial](TileSetChunkMaterial)s to the gpu
+const TILESET_CHUNK_MATERIAL_NODE: &str = "tileset_chunk_material";
+///The name of the node that sends the [TileSetGridMaterial](TileSetGridMaterial)s to the gpu
+const TILESET_GRID_MATERIAL_NODE: &str = "tileset_grid_material";
+///The name of the node that sends the [MarchingAntsMaterial](MarchingAntsMaterial)s to the gpu
+const MARCHING_ANTS_MATERIAL_NODE: &str = "marching_ants_material";
 
-fn setup_tile_pipeline(
+fn setup_tileset_pipelines(
     mut render_pipelines: ResMut<Assets<PipelineDescriptor>>,
     mut shaders: ResMut<Assets<Shader>>,
+    mut render_graph: ResMut<RenderGraph>,
 ) {
     //If the sprite's original render pipeline already exists
     if let Some(original_sprite_render_pipeline) =
         render_pipelines.get(bevy::sprite::SPRITE_PIPELINE_HANDLE)
     {
-        //We can clone the pipeline so we won't have to copy the code for setting it up
-        let mut pipeline_clone = original_sprite_render_pipeline.clone();
-        //In this example, we only want to override the fragment shader and so:
-        pipeline_clone.shader_stages.fragment = Some(shaders.add(Shader::from_glsl(
-            ShaderStage::Fragment,
-            include_str!("sprite.frag"),
-        )));
-        //Adding our custom pipeline and making it untracked so it won't get removed automatically when no sprite uses it
-        render_pipelines.set_untracked(CUSTOM_SPRITE_PIPELINE_HANDLE, pipeline_clone);
+        //We can clone the pipeline so we won't have to copy the code for setting up it's blending
+        let mut chunk_pipeline = original_sprite_render_pipeline.clone();
+        let mut grid_pipeline = original_sprite_render_pipeline.clone();
+        let mut marching_ants_pipeline = original_sprite_render_pipeline.clone();
+        //Chunks and grids are meshes with their own size and material, so both shaders are ours
+        let vertex_shader = shaders.add(Shader::from_glsl(
+            ShaderStage::Vertex,
+            include_str!("tileset_mesh.vert"),
+        ));
+        chunk_pipeline.shader_stages = ShaderStages {
+            vertex: vertex_shader.clone(),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("tileset_chunk.frag"),
+            ))),
+        };
+        grid_pipeline.shader_stages = ShaderStages {
+            vertex: vertex_shader,
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("tileset_grid.frag"),
+            ))),
+        };
+        //The selections' outlines are pushed out by their width in the vertex shader, so they have their own vertex shader
+        marching_ants_pipeline.shader_stages = ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("marching_ants.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("marching_ants.frag"),
+            ))),
+        };
+        //Adding our custom pipelines and making them untracked so they won't get removed automatically when nothing uses them
+        render_pipelines.set_untracked(TILESET_CHUNK_PIPELINE_HANDLE, chunk_pipeline);
+        render_pipelines.set_untracked(TILESET_GRID_PIPELINE_HANDLE, grid_pipeline);
+        render_pipelines.set_untracked(MARCHING_ANTS_PIPELINE_HANDLE, marching_ants_pipeline);
     }
+    //The materials are bound by their name in the shaders
+    render_graph.add_system_node(
+        TILESET_CHUNK_MATERIAL_NODE,
+        AssetRenderResourcesNode::<TileSetChunkMaterial>::new(true),
+    );
+    render_graph
+        .add_node_edge(TILESET_CHUNK_MATERIAL_NODE, base::node::MAIN_PASS)
+        .unwrap();
+    render_graph.add_system_node(
+        TILESET_GRID_MATERIAL_NODE,
+        AssetRenderResourcesNode::<TileSetGridMaterial>::new(true),
+    );
+    render_graph
+        .add_node_edge(TILESET_GRID_MATERIAL_NODE, base::node::MAIN_PASS)
+        .unwrap();
+    render_graph.add_system_node(
+        MARCHING_ANTS_MATERIAL_NODE,
+        AssetRenderResourcesNode::<MarchingAntsMaterial>::new(true),
+    );
+    render_graph
+        .add_node_edge(MARCHING_ANTS_MATERIAL_NODE, base::node::MAIN_PASS)
+        .unwrap();
 }
 ///Spawns The Cameras Needed for the editor
 fn spawn_cameras_system(mut commands: Commands) {
@@ -242,6 +545,15 @@ fn setup_tools(mut commands: Commands, mut pattern_assets: ResMut<Assets<Pattern
     });
     commands.insert_resource(data::resources::SelectedTool::Pencil { pattern_handle })
 }
+///Makes the asset server reload assets like [Pattern](Pattern)s when their files change
+fn watch_for_asset_changes(asset_server: Res<AssetServer>) {
+    if let Err(error) = asset_server.watch_for_changes() {
+        warn!(
+            "Assets won't be reloaded when their files change: {:?}",
+            error
+        );
+    }
+}
 /*
 fn debug_mouse_position_with_shape(
     input: Res<Input<MouseButton>>,
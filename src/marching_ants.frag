@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in float v_Distance;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 2, binding = 1) uniform MarchingAntsMaterial_dash_offset {
+    float DashOffset;
+};
+layout(set = 2, binding = 2) uniform MarchingAntsMaterial_dash_length {
+    float DashLength;
+};
+
+void main() {
+    //Every segment of the outline is a single dash color, the distance is the same on all of it's vertices
+    float dash = mod(floor(v_Distance + 0.5) - DashOffset, DashLength * 2.0);
+    if(dash < DashLength){
+        o_Target = vec4(1.0, 1.0, 1.0, 1.0);
+    }
+    else{
+        o_Target = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+}
@@ -0,0 +1,25 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec2 Vertex_Offset;
+layout(location = 2) in float Vertex_Distance;
+
+layout(location = 0) out float v_Distance;
+
+layout(set = 0, binding = 0) uniform CameraViewProj {
+    mat4 ViewProj;
+};
+
+layout(set = 1, binding = 0) uniform Transform {
+    mat4 Model;
+};
+
+layout(set = 2, binding = 0) uniform MarchingAntsMaterial_line_width {
+    float LineWidth;
+};
+
+void main() {
+    //The vertices are on the pixels' corners, they're pushed out by the line's width so it keeps it's width at every zoom
+    v_Distance = Vertex_Distance;
+    gl_Position = ViewProj * Model * vec4(Vertex_Position + vec3(Vertex_Offset * LineWidth, 0.0), 1.0);
+}
@@ -0,0 +1,280 @@
+use crate::data::{
+    animations::{AnimationPlayback, OnionSkinOverlay, OnionSkinSettings, TileSetAnimations},
+    layers::{blend_pixels, BlendMode, TileLayers, TileSetLayers},
+    resources::MousePixelPosition,
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TilePosition, TileSettings},
+    tileset_entity::TileSetSettings,
+};
+use bevy::{
+    prelude::*,
+    render::texture::{Extent3d, TextureDimension, TextureFormat},
+};
+///Moves the previewed animation forward by the time that passed since the last frame
+pub fn advance_animation_playback(
+    time: Res<Time>,
+    mut playback: ResMut<AnimationPlayback>,
+    tileset_query: Query<&TileSetAnimations, With<CurrentlySelected>>,
+) {
+    if !playback.playing {
+        return;
+    }
+    let animation = match (playback.animation, tileset_query.single()) {
+        (Some(animation_index), Ok(tileset_animations)) => {
+            match tileset_animations.animations.get(animation_index) {
+                Some(animation) if !animation.frames.is_empty() => animation,
+                _ => return,
+            }
+        }
+        _ => return,
+    };
+    //The frames could have been removed while playing
+    if playback.frame >= animation.frames.len() {
+        playback.rewind();
+    }
+    playback.frame_elapsed_seconds += time.delta_seconds();
+    loop {
+        //Frames with no duration would never let the loop end
+        let frame_duration =
+            u32::max(animation.frames[playback.frame].duration_ms, 1) as f32 / 1000.0;
+        if playback.frame_elapsed_seconds < frame_duration {
+            break;
+        }
+        playback.frame_elapsed_seconds -= frame_duration;
+        playback.frame = (playback.frame + 1) % animation.frames.len();
+    }
+}
+///The sprite showing the onion skin and the tile it was last shown for
+#[derive(Debug, Default)]
+pub struct OnionSkinState {
+    overlay: Option<Entity>,
+    ///The tileset and the position of the tile the onion skin was last shown for
+    edited_tile: Option<(Entity, UVec2)>,
+}
+///Shows the tiles around the hovered tile under it, tinted and faded, while onion skinning is enabled
+///
+///The onion skin is a sprite on top of the tile that only shows where the tile is transparent
+pub fn update_onion_skin(
+    mut commands: Commands,
+    mut onion_skin_state: Local<OnionSkinState>,
+    onion_skin_settings: Res<OnionSkinSettings>,
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tileset_query: Query<
+        (
+            Entity,
+            &TileSetSettings,
+            &TileSetLayers,
+            &Children,
+            ChangeTrackers<TileSetLayers>,
+        ),
+        With<CurrentlySelected>,
+    >,
+    tile_query: Query<(
+        &TilePosition,
+        &TileSettings,
+        &TileData,
+        &TileLayers,
+        &GlobalTransform,
+        ChangeTrackers<TileData>,
+        ChangeTrackers<TileLayers>,
+    )>,
+    mut overlay_query: Query<
+        (&mut Transform, &mut Visible, &Handle<ColorMaterial>),
+        With<OnionSkinOverlay>,
+    >,
+) {
+    let (tileset_entity, tileset_settings, tileset_layers, children, tileset_layers_tracker) =
+        match tileset_query.single() {
+            Ok(tileset) if onion_skin_settings.enabled => tileset,
+            _ => {
+                hide_onion_skin(&onion_skin_state, &mut overlay_query);
+                return;
+            }
+        };
+    //The edited tile is the hovered one, it stays the same while the mouse is outside of the tileset
+    let tile_size = UVec2::new(
+        tileset_settings.tile_width as u32,
+        tileset_settings.tile_height as u32,
+    );
+    let edited_tile = match mouse_pixel_position.pixel_position {
+        Some(mouse_pixel) => Some((tileset_entity, mouse_pixel / tile_size)),
+        None => onion_skin_state
+            .edited_tile
+            .filter(|(edited_tileset, _)| *edited_tileset == tileset_entity),
+    };
+    let edited_position = match edited_tile {
+        Some((_, edited_position)) => edited_position,
+        None => {
+            hide_onion_skin(&onion_skin_state, &mut overlay_query);
+            return;
+        }
+    };
+    //The edited tile is in the middle, the tiles before it are on the left
+    let frames = onion_skin_settings.frames;
+    let edited_order = get_tile_order(tileset_settings, edited_position) as i64;
+    let mut tiles: Vec<_> = (0..frames * 2 + 1).map(|_| None).collect();
+    for child in children.iter() {
+        if let Ok(tile) = tile_query.get(*child) {
+            let offset = get_tile_order(tileset_settings, tile.0.position) as i64 - edited_order;
+            if offset.abs() <= frames as i64 {
+                tiles[(offset + frames as i64) as usize] = Some(tile);
+            }
+        }
+    }
+    let (tile_settings, tile_data, tile_layers, global_transform) = match &tiles[frames] {
+        Some((_, tile_settings, tile_data, tile_layers, global_transform, _, _)) => {
+            (*tile_settings, *tile_data, *tile_layers, *global_transform)
+        }
+        None => {
+            hide_onion_skin(&onion_skin_state, &mut overlay_query);
+            return;
+        }
+    };
+    //The onion skin only needs to be rebuilt when something it shows changed
+    let tiles_changed =
+        tiles
+            .iter()
+            .flatten()
+            .any(|(_, _, _, _, _, tile_data_tracker, tile_layers_tracker)| {
+                tile_data_tracker.is_changed() || tile_layers_tracker.is_changed()
+            });
+    let overlay = onion_skin_state
+        .overlay
+        .filter(|overlay| overlay_query.get_mut(*overlay).is_ok());
+    if overlay.is_some()
+        && onion_skin_state.edited_tile == edited_tile
+        && !onion_skin_settings.is_changed()
+        && !tileset_layers_tracker.is_changed()
+        && !tiles_changed
+    {
+        return;
+    }
+    onion_skin_state.edited_tile = edited_tile;
+    let mut pixels = vec![0; tile_settings.tile_width * tile_settings.tile_height * 4];
+    //The furthest tiles go first so the closer ones are drawn over them
+    for distance in (1..=frames).rev() {
+        let opacity =
+            (onion_skin_settings.opacity as usize * (frames - distance + 1) / frames) as u8;
+        for (tile, tint) in [
+            (&tiles[frames - distance], onion_skin_settings.previous_tint),
+            (&tiles[frames + distance], onion_skin_settings.next_tint),
+        ]
+        .iter()
+        {
+            if let Some((_, _, other_data, other_layers, _, _, _)) = tile {
+                let composite = other_layers.composite(tileset_layers, other_data);
+                for (below, above) in pixels.chunks_exact_mut(4).zip(composite.chunks_exact(4)) {
+                    let blended = blend_pixels(
+                        [below[0], below[1], below[2], below[3]],
+                        tint_pixel([above[0], above[1], above[2], above[3]], *tint),
+                        BlendMode::Normal,
+                        opacity,
+                    );
+                    below.copy_from_slice(&blended);
+                }
+            }
+        }
+    }
+    //The onion skin is under the edited tile, so it's hidden by the tile's pixels
+    let edited_composite = tile_layers.composite(tileset_layers, tile_data);
+    for (pixel, edited_pixel) in pixels
+        .chunks_exact_mut(4)
+        .zip(edited_composite.chunks_exact(4))
+    {
+        pixel[3] = (pixel[3] as u32 * (u8::MAX - edited_pixel[3]) as u32 / u8::MAX as u32) as u8;
+    }
+    let size = Extent3d::new(
+        tile_settings.tile_width as u32,
+        tile_settings.tile_height as u32,
+        1,
+    );
+    //Slightly in front of the tile
+    let transform = Transform {
+        translation: global_transform.translation + Vec3::Z,
+        scale: global_transform.scale,
+        ..Default::default()
+    };
+    match overlay {
+        Some(overlay) => {
+            let (mut overlay_transform, mut visible, material_handle) =
+                overlay_query.get_mut(overlay).unwrap();
+            *overlay_transform = transform;
+            visible.is_visible = true;
+            //The overlay keeps it's texture, only it's pixels are replaced
+            let texture_handle = materials
+                .get(material_handle)
+                .and_then(|material| material.texture.clone());
+            match texture_handle.and_then(|texture_handle| textures.get_mut(texture_handle)) {
+                Some(texture) => {
+                    //The tiles of the newly selected tileset can have another size
+                    if texture.size != size {
+                        texture.size = size;
+                    }
+                    texture.data = pixels;
+                }
+                None => {
+                    if let Some(material) = materials.get_mut(material_handle) {
+                        material.texture = Some(textures.add(get_onion_skin_texture(size, pixels)));
+                    }
+                }
+            }
+        }
+        None => {
+            let texture_handle = textures.add(get_onion_skin_texture(size, pixels));
+            onion_skin_state.overlay = Some(
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        material: materials.add(ColorMaterial::texture(texture_handle)),
+                        transform,
+                        ..Default::default()
+                    })
+                    .insert(OnionSkinOverlay)
+                    .id(),
+            );
+        }
+    }
+}
+///Returns the texture the onion skin's sprite shows
+fn get_onion_skin_texture(size: Extent3d, pixels: Vec<u8>) -> Texture {
+    Texture::new(
+        size,
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+///Hides the onion skin's sprite until it's needed again
+fn hide_onion_skin(
+    onion_skin_state: &OnionSkinState,
+    overlay_query: &mut Query<
+        (&mut Transform, &mut Visible, &Handle<ColorMaterial>),
+        With<OnionSkinOverlay>,
+    >,
+) {
+    if let Some(Ok((_, mut visible, _))) = onion_skin_state
+        .overlay
+        .map(|overlay| overlay_query.get_mut(overlay))
+    {
+        if visible.is_visible {
+            visible.is_visible = false;
+        }
+    }
+}
+///Returns where a tile is when the tiles are read like text, from left to right and from the top row down
+fn get_tile_order(tileset_settings: &TileSetSettings, tile_position: UVec2) -> usize {
+    (tileset_settings.tileset_height - 1 - tile_position.y as usize)
+        * tileset_settings.tileset_width
+        + tile_position.x as usize
+}
+///Mixes a pixel's color with a tint by the tint's alpha, the pixel keeps it's alpha
+fn tint_pixel(pixel: [u8; 4], tint: [u8; 4]) -> [u8; 4] {
+    let mut tinted = pixel;
+    for channel in 0..3 {
+        tinted[channel] = ((pixel[channel] as u32 * (u8::MAX - tint[3]) as u32
+            + tint[channel] as u32 * tint[3] as u32)
+            / u8::MAX as u32) as u8;
+    }
+    tinted
+}
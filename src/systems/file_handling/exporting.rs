@@ -0,0 +1,602 @@
+use super::project::read_project_file;
+use crate::data::{
+    events::{ExportAnimationEvent, ExportTileSetEvent},
+    layers::{LayerPixels, TileLayers, TileSetLayers},
+    project::TileSetDocument,
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TilePosition},
+    tileset_entity::TileSetSettings,
+};
+use bevy::{prelude::*, utils::HashMap};
+use image::{ColorType, ImageFormat};
+use std::{
+    error::Error,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+///Writes the currently selected [TileSetBundle](crate::data::tileset_entity::TileSetBundle) to disk as a single png sprite sheet
+pub fn export_selected_tileset(
+    mut export_events: EventReader<ExportTileSetEvent>,
+    tileset_query: Query<(&TileSetSettings, &TileSetLayers, &Children), With<CurrentlySelected>>,
+    tile_query: Query<(&TilePosition, &TileData, &TileLayers)>,
+) {
+    for event in export_events.iter() {
+        if let Ok((tileset_settings, tileset_layers, children)) = tileset_query.single() {
+            //The image has the tiles like they're shown, with their visible layers blended together
+            let composited_tiles: Vec<(TilePosition, TileData)> = children
+                .iter()
+                .filter_map(|child| tile_query.get(*child).ok())
+                .map(|(tile_position, tile_data, tile_layers)| {
+                    (
+                        tile_position.clone(),
+                        TileData {
+                            data: tile_layers.composite(tileset_layers, tile_data),
+                        },
+                    )
+                })
+                .collect();
+            let image_data = compose_tileset_image(
+                tileset_settings,
+                composited_tiles
+                    .iter()
+                    .map(|(tile_position, tile_data)| (tile_position, tile_data)),
+            );
+            let (image_width, image_height) = get_tileset_image_size(tileset_settings);
+            if let Err(error) = image::save_buffer_with_format(
+                &event.path,
+                &image_data,
+                image_width,
+                image_height,
+                ColorType::Rgba8,
+                ImageFormat::Png,
+            ) {
+                error!("Couldn't export the tileset to {:?}: {}", event.path, error);
+            }
+        }
+    }
+}
+///Returns the size in pixels of the image a [TileSetBundle](crate::data::tileset_entity::TileSetBundle) is exported to
+pub fn get_tileset_image_size(tileset_settings: &TileSetSettings) -> (u32, u32) {
+    (
+        (tileset_settings.tileset_width * tileset_settings.tile_width) as u32,
+        (tileset_settings.tileset_height * tileset_settings.tile_height) as u32,
+    )
+}
+///Places the [TileData](TileData) of every tile by it's [TilePosition](TilePosition) in a single RGBA8 buffer
+///
+///The buffer's rows go from the top of the tileset to the bottom, like in any image file
+pub fn compose_tileset_image<'a>(
+    tileset_settings: &TileSetSettings,
+    tiles: impl Iterator<Item = (&'a TilePosition, &'a TileData)>,
+) -> Vec<u8> {
+    let (image_width, image_height) = get_tileset_image_size(tileset_settings);
+    let mut image_data = vec![0; image_width as usize * image_height as usize * 4];
+    let tile_row_length = tileset_settings.tile_width * 4;
+    for (tile_position, tile_data) in tiles {
+        //Blank tiles have no data, they stay transparent
+        if tile_data.data.len() != tile_row_length * tileset_settings.tile_height {
+            continue;
+        }
+        //Tile positions start at the bottom of the tileset, but images start at the top
+        let first_image_row =
+            (tileset_settings.tileset_height - 1 - tile_position.position.y as usize)
+                * tileset_settings.tile_height;
+        let first_image_column = tile_position.position.x as usize * tileset_settings.tile_width;
+        //The tile's data rows are already stored from top to bottom
+        for (tile_row, row) in tile_data.data.chunks_exact(tile_row_length).enumerate() {
+            let start =
+                ((first_image_row + tile_row) * image_width as usize + first_image_column) * 4;
+            image_data[start..start + tile_row_length].copy_from_slice(row);
+        }
+    }
+    image_data
+}
+///Writes some tiles of the currently selected [TileSetBundle](crate::data::tileset_entity::TileSetBundle) to disk as an animation
+pub fn export_selected_tileset_animation(
+    mut export_animation_events: EventReader<ExportAnimationEvent>,
+    tileset_query: Query<(&TileSetSettings, &TileSetLayers, &Children), With<CurrentlySelected>>,
+    tile_query: Query<(&TilePosition, &TileData, &TileLayers)>,
+) {
+    for event in export_animation_events.iter() {
+        if let Ok((tileset_settings, tileset_layers, children)) = tileset_query.single() {
+            //The frames show the tiles like they're shown, with their visible layers blended together
+            let frames = event
+                .tiles
+                .iter()
+                .map(|frame_tile| {
+                    let pixels = children
+                        .iter()
+                        .filter_map(|child| tile_query.get(*child).ok())
+                        .find(|(tile_position, ..)| tile_position.position == *frame_tile)
+                        .map(|(_, tile_data, tile_layers)| {
+                            tile_layers.composite(tileset_layers, tile_data)
+                        });
+                    AnimationImageFrame {
+                        pixels: get_frame_pixels(tileset_settings, pixels),
+                        duration_ms: event.frame_delay_ms,
+                    }
+                })
+                .collect::<Vec<_>>();
+            if let Err(error) = write_animation_file(
+                &event.path,
+                tileset_settings.tile_width as u32,
+                tileset_settings.tile_height as u32,
+                &frames,
+            ) {
+                error!(
+                    "Couldn't export the animation to {:?}: {}",
+                    event.path, error
+                );
+            }
+        }
+    }
+}
+///Exports an animation from a project file without opening a window
+///
+///The arguments are `<project> <tileset> <output> <frame delay ms> <x,y>...`, the tileset is it's name or index
+pub fn run_export_animation_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (project_path, tileset, output_path, frame_delay_ms, tiles) = match args {
+        [project_path, tileset, output_path, frame_delay_ms, tiles @ ..] if !tiles.is_empty() => {
+            (project_path, tileset, output_path, frame_delay_ms, tiles)
+        }
+        _ => {
+            return Err(
+                "usage: export-animation <project> <tileset> <output> <frame delay ms> <x,y>..."
+                    .into(),
+            )
+        }
+    };
+    let document = read_project_file(Path::new(project_path))?;
+    let tileset_document = document
+        .tilesets
+        .iter()
+        .find(|tileset_document| tileset_document.name == *tileset)
+        .or_else(|| {
+            tileset
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| document.tilesets.get(index))
+        })
+        .ok_or_else(|| format!("the project has no tileset named {}", tileset))?;
+    let tiles = parse_tile_positions(&tiles.join(" "))?;
+    let frame_delay_ms = frame_delay_ms.parse::<u32>()?;
+    let settings = &tileset_document.settings;
+    let frames: Vec<_> = tiles
+        .iter()
+        .map(|tile| AnimationImageFrame {
+            pixels: get_frame_pixels(settings, get_document_tile_pixels(tileset_document, *tile)),
+            duration_ms: frame_delay_ms,
+        })
+        .collect();
+    write_animation_file(
+        &PathBuf::from(output_path),
+        settings.tile_width as u32,
+        settings.tile_height as u32,
+        &frames,
+    )
+}
+///Reads tile positions written as `x,y` and separated by spaces, like `0,1 1,1 2,1`
+pub fn parse_tile_positions(text: &str) -> Result<Vec<UVec2>, Box<dyn Error>> {
+    text.split_whitespace()
+        .map(|tile| -> Result<UVec2, Box<dyn Error>> {
+            let mut coordinates = tile.split(',');
+            match (coordinates.next(), coordinates.next(), coordinates.next()) {
+                (Some(x), Some(y), None) => Ok(UVec2::new(x.trim().parse()?, y.trim().parse()?)),
+                _ => Err(format!("{} isn't a tile position like x,y", tile).into()),
+            }
+        })
+        .collect()
+}
+///Blends the layers of a tile in a [TileSetDocument](TileSetDocument) together, None if there's no such tile
+fn get_document_tile_pixels(
+    tileset_document: &TileSetDocument,
+    position: UVec2,
+) -> Option<Vec<u8>> {
+    let tile_document = tileset_document
+        .tiles
+        .iter()
+        .find(|tile_document| tile_document.position == [position.x, position.y])?;
+    //The bottom layer acts as the active one, so it's pixels go in the tile's data
+    let mut tileset_layers = TileSetLayers::default();
+    if !tileset_document.layers.is_empty() {
+        tileset_layers.layers = tileset_document.layers.clone();
+    }
+    let tile_layers = TileLayers {
+        layers: std::iter::once(LayerPixels::default())
+            .chain(tile_document.layers.iter().map(|layer_data| LayerPixels {
+                data: layer_data.clone(),
+                indices: Vec::new(),
+            }))
+            .collect(),
+    };
+    let tile_data = TileData {
+        data: tile_document.data.clone(),
+    };
+    Some(tile_layers.composite(&tileset_layers, &tile_data))
+}
+///Missing tiles and tiles that weren't initiated yet are shown as transparent frames
+fn get_frame_pixels(tileset_settings: &TileSetSettings, pixels: Option<Vec<u8>>) -> Vec<u8> {
+    let frame_length = tileset_settings.tile_width * tileset_settings.tile_height * 4;
+    match pixels {
+        Some(pixels) if pixels.len() == frame_length => pixels,
+        _ => vec![0; frame_length],
+    }
+}
+///A single image of an exported animation
+#[derive(Debug, Clone)]
+pub struct AnimationImageFrame {
+    ///RGBA8 pixels, rows from top to bottom like in any image file
+    pub pixels: Vec<u8>,
+    pub duration_ms: u32,
+}
+///The animated image formats we can write, picked by the file's extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+    ///Up to 256 colors per frame and fully transparent or opaque pixels
+    Gif,
+    ///An animated png, it keeps every color and partially transparent pixels
+    Apng,
+}
+impl AnimationFormat {
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("gif") => Ok(AnimationFormat::Gif),
+            Some("png") | Some("apng") => Ok(AnimationFormat::Apng),
+            _ => Err("animations have to be .gif, .png or .apng files".into()),
+        }
+    }
+}
+///Writes the frames as an animation that loops forever, the format is picked by the path's extension
+pub fn write_animation_file(
+    path: &Path,
+    width: u32,
+    height: u32,
+    frames: &[AnimationImageFrame],
+) -> Result<(), Box<dyn Error>> {
+    if frames.is_empty() {
+        return Err("an animation needs at least one frame".into());
+    }
+    let format = AnimationFormat::from_path(path)?;
+    let writer = BufWriter::new(File::create(path)?);
+    match format {
+        AnimationFormat::Gif => write_gif(writer, width, height, frames),
+        AnimationFormat::Apng => write_apng(writer, width, height, frames),
+    }
+}
+///Gif pixels with an alpha under this are transparent, the rest are opaque
+const GIF_ALPHA_THRESHOLD: u8 = 128;
+///Writes the frames as a gif
+///
+///When all of the frames have up to 255 colors together they share an exact palette, the index after their colors is transparent.
+///Otherwise every frame's colors are reduced to it's own palette
+pub fn write_gif(
+    writer: impl std::io::Write,
+    width: u32,
+    height: u32,
+    frames: &[AnimationImageFrame],
+) -> Result<(), Box<dyn Error>> {
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err("gifs can't be bigger than 65535 pixels".into());
+    }
+    let (width, height) = (width as u16, height as u16);
+    let palette = get_exact_gif_palette(frames);
+    //The transparent index needs a color in the table too, the encoder only pads the table up to a power of two
+    let global_palette: Vec<u8> = palette
+        .as_ref()
+        .map(|palette| {
+            palette
+                .iter()
+                .chain(std::iter::once(&[0, 0, 0]))
+                .flat_map(|color| color.iter().copied())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut encoder = gif::Encoder::new(writer, width, height, &global_palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for frame in frames {
+        let mut gif_frame = match &palette {
+            Some(palette) => {
+                let color_indices: HashMap<[u8; 3], u8> = palette
+                    .iter()
+                    .enumerate()
+                    .map(|(index, color)| (*color, index as u8))
+                    .collect();
+                let transparent_index = palette.len() as u8;
+                let buffer: Vec<u8> = frame
+                    .pixels
+                    .chunks_exact(4)
+                    .map(|pixel| {
+                        if pixel[3] < GIF_ALPHA_THRESHOLD {
+                            transparent_index
+                        } else {
+                            color_indices[&[pixel[0], pixel[1], pixel[2]]]
+                        }
+                    })
+                    .collect();
+                gif::Frame {
+                    width,
+                    height,
+                    buffer: buffer.into(),
+                    transparent: Some(transparent_index),
+                    ..Default::default()
+                }
+            }
+            None => {
+                //The quantizer only knows fully transparent pixels
+                let mut pixels = frame.pixels.clone();
+                for pixel in pixels.chunks_exact_mut(4) {
+                    pixel[3] = if pixel[3] < GIF_ALPHA_THRESHOLD {
+                        0
+                    } else {
+                        u8::MAX
+                    };
+                }
+                gif::Frame::from_rgba_speed(width, height, &mut pixels, 10)
+            }
+        };
+        //Gif delays are in hundredths of a second
+        gif_frame.delay = u32::min((frame.duration_ms + 5) / 10, u16::MAX as u32) as u16;
+        //Transparent pixels would show the last frame otherwise
+        gif_frame.dispose = gif::DisposalMethod::Background;
+        encoder.write_frame(&gif_frame)?;
+    }
+    Ok(())
+}
+///Returns every opaque color used in the frames, None if they don't fit in a gif palette next to the transparent color
+fn get_exact_gif_palette(frames: &[AnimationImageFrame]) -> Option<Vec<[u8; 3]>> {
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    for pixel in frames.iter().flat_map(|frame| frame.pixels.chunks_exact(4)) {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        if pixel[3] >= GIF_ALPHA_THRESHOLD && !palette.contains(&color) {
+            if palette.len() == u8::MAX as usize {
+                return None;
+            }
+            palette.push(color);
+        }
+    }
+    Some(palette)
+}
+///Writes the frames as an animated png
+pub fn write_apng(
+    writer: impl std::io::Write,
+    width: u32,
+    height: u32,
+    frames: &[AnimationImageFrame],
+) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    //Zero plays means looping forever
+    encoder.set_animated(frames.len() as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+    //Every frame replaces the whole image, transparent pixels included
+    writer.set_blend_op(png::BlendOp::Source)?;
+    writer.set_dispose_op(png::DisposeOp::Background)?;
+    for frame in frames {
+        writer.set_frame_delay(u32::min(frame.duration_ms, u16::MAX as u32) as u16, 1000)?;
+        writer.write_image_data(&frame.pixels)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::project::{ProjectDocument, TileDocument, TileSetViewDocument},
+        systems::file_handling::project::write_project_file,
+    };
+
+    const WIDTH: u32 = 2;
+    const HEIGHT: u32 = 2;
+    ///A frame with a color in it's top left pixel and transparent pixels everywhere else
+    fn frame_with_pixel(color: [u8; 4], duration_ms: u32) -> AnimationImageFrame {
+        let mut pixels = vec![0; (WIDTH * HEIGHT * 4) as usize];
+        pixels[..4].copy_from_slice(&color);
+        AnimationImageFrame {
+            pixels,
+            duration_ms,
+        }
+    }
+    ///A frame where every pixel has a different opaque color, starting from some index
+    fn frame_with_colors(width: u32, height: u32, first_color: u32) -> AnimationImageFrame {
+        AnimationImageFrame {
+            pixels: (first_color..first_color + width * height)
+                .flat_map(|color| vec![(color % 256) as u8, (color / 256) as u8, 7, 255])
+                .collect(),
+            duration_ms: 100,
+        }
+    }
+    ///Decodes a gif's frames as RGBA8 pixels with their delays
+    fn decode_gif(bytes: &[u8]) -> Vec<(Vec<u8>, u16)> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(bytes).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.buffer.to_vec(), frame.delay));
+        }
+        frames
+    }
+    fn encode_gif(width: u32, height: u32, frames: &[AnimationImageFrame]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_gif(&mut bytes, width, height, frames).unwrap();
+        bytes
+    }
+    #[test]
+    fn gifs_keep_their_frames_delays_and_transparent_pixels() {
+        let frames = vec![
+            frame_with_pixel([255, 0, 0, 255], 100),
+            frame_with_pixel([0, 255, 0, 255], 250),
+            frame_with_pixel([0, 0, 255, 100], 30),
+        ];
+        let decoded = decode_gif(&encode_gif(WIDTH, HEIGHT, &frames));
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(
+            decoded.iter().map(|(_, delay)| *delay).collect::<Vec<_>>(),
+            vec![10, 25, 3]
+        );
+        assert_eq!(&decoded[0].0[..4], &[255, 0, 0, 255]);
+        assert_eq!(&decoded[1].0[..4], &[0, 255, 0, 255]);
+        //Half transparent pixels become transparent
+        assert_eq!(decoded[2].0[3], 0);
+        for (pixels, _) in decoded.iter() {
+            for pixel in pixels.chunks_exact(4).skip(1) {
+                assert_eq!(pixel[3], 0);
+            }
+        }
+    }
+    #[test]
+    fn gif_transparent_index_is_in_the_color_table_with_power_of_two_colors() {
+        for color_count in [1, 2, 4, 8, 16, 32, 64, 128].iter() {
+            let frame = frame_with_colors(*color_count, 1, 0);
+            let mut transparent_frame = frame_with_colors(*color_count, 1, 0);
+            transparent_frame.pixels[3] = 0;
+            let bytes = encode_gif(*color_count, 1, &[frame, transparent_frame]);
+            let mut options = gif::DecodeOptions::new();
+            options.set_color_output(gif::ColorOutput::Indexed);
+            let mut decoder = options.read_info(&bytes[..]).unwrap();
+            let table_length = decoder.global_palette().unwrap().len() / 3;
+            while let Some(frame) = decoder.read_next_frame().unwrap() {
+                let transparent_index = frame.transparent.unwrap() as usize;
+                assert!(transparent_index < table_length);
+                assert_eq!(transparent_index, *color_count as usize);
+            }
+            let decoded = decode_gif(&bytes);
+            assert_eq!(decoded[1].0[3], 0);
+        }
+    }
+    #[test]
+    fn gifs_with_more_than_255_colors_are_quantized() {
+        let frames = vec![frame_with_colors(20, 15, 0), frame_with_colors(20, 15, 300)];
+        let bytes = encode_gif(20, 15, &frames);
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(&bytes[..]).unwrap();
+        assert!(decoder
+            .global_palette()
+            .map_or(true, |palette| palette.is_empty()));
+        let mut frame_count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            //Every frame has it's own palette
+            assert!(frame.palette.is_some());
+            assert_eq!(frame.delay, 10);
+            frame_count += 1;
+        }
+        assert_eq!(frame_count, 2);
+        //The colors are close to the original ones
+        let decoded = decode_gif(&bytes);
+        for (decoded_pixel, pixel) in decoded[0]
+            .0
+            .chunks_exact(4)
+            .zip(frames[0].pixels.chunks_exact(4))
+        {
+            assert_eq!(decoded_pixel[3], 255);
+            for channel in 0..3 {
+                assert!((decoded_pixel[channel] as i32 - pixel[channel] as i32).abs() < 48);
+            }
+        }
+    }
+    #[test]
+    fn apngs_keep_their_frames_delays_and_partial_transparency() {
+        let frames = vec![
+            frame_with_pixel([255, 0, 0, 255], 100),
+            frame_with_pixel([0, 0, 255, 100], 250),
+        ];
+        let mut bytes = Vec::new();
+        write_apng(&mut bytes, WIDTH, HEIGHT, &frames).unwrap();
+        let mut reader = png::Decoder::new(&bytes[..]).read_info().unwrap();
+        let animation_control = reader.info().animation_control.unwrap();
+        assert_eq!(animation_control.num_frames, 2);
+        assert_eq!(animation_control.num_plays, 0);
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        for frame in frames.iter() {
+            reader.next_frame(&mut buffer).unwrap();
+            let frame_control = reader.info().frame_control.unwrap();
+            assert_eq!(
+                (frame_control.delay_num, frame_control.delay_den),
+                (frame.duration_ms as u16, 1000)
+            );
+            assert_eq!(buffer, frame.pixels);
+        }
+    }
+    #[test]
+    fn the_command_exports_tiles_from_a_project_file() {
+        let directory = std::env::temp_dir();
+        let project_path = directory.join("sprite_editor_export_test.ron");
+        let output_path = directory.join("sprite_editor_export_test.gif");
+        let tile_pixels = |color: [u8; 4]| {
+            let mut pixels = vec![0; (WIDTH * HEIGHT * 4) as usize];
+            pixels[..4].copy_from_slice(&color);
+            pixels
+        };
+        let document = ProjectDocument {
+            tilesets: vec![TileSetDocument {
+                name: "Walk".to_string(),
+                settings: TileSetSettings {
+                    tile_width: WIDTH as usize,
+                    tile_height: HEIGHT as usize,
+                    tileset_width: 2,
+                    tileset_height: 1,
+                },
+                view: TileSetViewDocument {
+                    camera_translation: [0.0; 3],
+                    camera_scale: [1.0; 3],
+                },
+                tiles: vec![
+                    TileDocument {
+                        name: String::new(),
+                        position: [0, 0],
+                        data: tile_pixels([255, 0, 0, 255]),
+                        layers: Vec::new(),
+                        indices: Vec::new(),
+                        layer_indices: Vec::new(),
+                    },
+                    //Blank tiles export as transparent frames
+                    TileDocument {
+                        name: String::new(),
+                        position: [1, 0],
+                        data: Vec::new(),
+                        layers: Vec::new(),
+                        indices: Vec::new(),
+                        layer_indices: Vec::new(),
+                    },
+                ],
+                palette: None,
+                layers: Vec::new(),
+                animations: Vec::new(),
+            }],
+            selected_tileset: Some(0),
+        };
+        write_project_file(&project_path, &document).unwrap();
+        let args: Vec<String> = vec![
+            project_path.to_string_lossy().to_string(),
+            "Walk".to_string(),
+            output_path.to_string_lossy().to_string(),
+            "120".to_string(),
+            "0,0".to_string(),
+            "1,0".to_string(),
+            "0,0".to_string(),
+        ];
+        run_export_animation_command(&args).unwrap();
+        let decoded = decode_gif(&std::fs::read(&output_path).unwrap());
+        std::fs::remove_file(&project_path).unwrap();
+        std::fs::remove_file(&output_path).unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert!(decoded.iter().all(|(_, delay)| *delay == 12));
+        assert_eq!(&decoded[0].0[..4], &[255, 0, 0, 255]);
+        assert!(decoded[1].0.chunks_exact(4).all(|pixel| pixel[3] == 0));
+        assert_eq!(decoded[2].0, decoded[0].0);
+    }
+    #[test]
+    fn the_command_needs_tiles() {
+        let args: Vec<String> = vec!["project.ron".to_string(), "0".to_string()];
+        assert!(run_export_animation_command(&args).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::data::{
+    events::OpenImageEvent,
+    resources::TileSetOrderCounter,
+    tile_entity::TileData,
+    tileset_entity::{LoadedTile, LoadedTiles, TileSetBundle, TileSetSettings},
+};
+use bevy::prelude::*;
+use image::RgbaImage;
+
+///Opens png sprite sheets as new [TileSetBundle](TileSetBundle)s, sliced by the tile size the user chose
+pub fn open_image_as_tileset(
+    mut commands: Commands,
+    mut open_image_events: EventReader<OpenImageEvent>,
+    mut tileset_order_counter: ResMut<TileSetOrderCounter>,
+) {
+    for event in open_image_events.iter() {
+        let image = match image::open(&event.path) {
+            Ok(image) => image.into_rgba8(),
+            Err(error) => {
+                error!("Couldn't open the image {:?}: {}", event.path, error);
+                continue;
+            }
+        };
+        //The tileset is made big enough to hold the whole image, the tiles on the right and bottom edges may be partly transparent
+        let tileset_settings = TileSetSettings {
+            tile_width: event.tile_width,
+            tile_height: event.tile_height,
+            tileset_width: (image.width() as usize + event.tile_width - 1) / event.tile_width,
+            tileset_height: (image.height() as usize + event.tile_height - 1) / event.tile_height,
+        };
+        let tiles = slice_image_into_tiles(&image, &tileset_settings);
+        let name = event
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Image".to_string());
+        commands
+            .spawn_bundle(TileSetBundle::with_name(
+                tileset_settings,
+                name,
+                tileset_order_counter.next(),
+            ))
+            .insert(LoadedTiles { tiles });
+    }
+}
+///Cuts an image into the [TileData](TileData) of every tile in the tileset, ordered like [LoadedTiles](LoadedTiles)
+///
+///The image's top left corner is placed at the tileset's top left corner, this is the opposite of exporting
+pub fn slice_image_into_tiles(
+    image: &RgbaImage,
+    tileset_settings: &TileSetSettings,
+) -> Vec<LoadedTile> {
+    let mut tiles =
+        Vec::with_capacity(tileset_settings.tileset_width * tileset_settings.tileset_height);
+    for y_tileset in 0..tileset_settings.tileset_height {
+        for x_tileset in 0..tileset_settings.tileset_width {
+            let mut data = vec![0; tileset_settings.tile_width * tileset_settings.tile_height * 4];
+            //Tile positions start at the bottom of the tileset, but images start at the top
+            let first_image_row =
+                (tileset_settings.tileset_height - 1 - y_tileset) * tileset_settings.tile_height;
+            let first_image_column = x_tileset * tileset_settings.tile_width;
+            //Like in the image, the tile's data rows are stored from top to bottom
+            for tile_row in 0..tileset_settings.tile_height {
+                let image_row = (first_image_row + tile_row) as u32;
+                if image_row >= image.height() {
+                    break;
+                }
+                for tile_column in 0..tileset_settings.tile_width {
+                    let image_column = (first_image_column + tile_column) as u32;
+                    if image_column >= image.width() {
+                        break;
+                    }
+                    let start = (tile_row * tileset_settings.tile_width + tile_column) * 4;
+                    data[start..start + 4]
+                        .copy_from_slice(&image.get_pixel(image_column, image_row).0);
+                }
+            }
+            tiles.push(LoadedTile {
+                data: TileData { data },
+                ..Default::default()
+            });
+        }
+    }
+    tiles
+}
@@ -0,0 +1,4 @@
+pub mod exporting;
+pub mod importing;
+pub mod palette;
+pub mod project;
@@ -0,0 +1,397 @@
+use crate::data::{
+    events::{OpenPaletteEvent, SavePaletteEvent},
+    palette::{Palette, Swatch},
+};
+use bevy::prelude::*;
+use std::{error::Error, fs, path::Path};
+
+///Replaces the [Palette](Palette) with the one in a palette file
+pub fn open_palette(
+    mut open_palette_events: EventReader<OpenPaletteEvent>,
+    mut palette: ResMut<Palette>,
+) {
+    for event in open_palette_events.iter() {
+        match read_palette_file(&event.path) {
+            Ok(opened_palette) => *palette = opened_palette,
+            Err(error) => error!("Couldn't open the palette {:?}: {}", event.path, error),
+        }
+    }
+}
+///Writes the [Palette](Palette) to a palette file
+pub fn save_palette(mut save_palette_events: EventReader<SavePaletteEvent>, palette: Res<Palette>) {
+    for event in save_palette_events.iter() {
+        if let Err(error) = write_palette_file(&event.path, &palette) {
+            error!("Couldn't save the palette to {:?}: {}", event.path, error);
+        }
+    }
+}
+///The palette file formats we can read and write, picked by the file's extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteFormat {
+    ///GIMP's text format, it's the only one that keeps the swatches' names
+    Gpl,
+    ///Lospec's format, a color per line in RRGGBB hex
+    Hex,
+    ///JASC's text format, used by Paint Shop Pro and Aseprite
+    Pal,
+    ///Adobe's binary color table, 256 RGB colors
+    Act,
+}
+impl PaletteFormat {
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("gpl") => Ok(PaletteFormat::Gpl),
+            Some("hex") => Ok(PaletteFormat::Hex),
+            Some("pal") => Ok(PaletteFormat::Pal),
+            Some("act") => Ok(PaletteFormat::Act),
+            _ => Err("palettes have to be .gpl, .hex, .pal or .act files".into()),
+        }
+    }
+}
+pub fn read_palette_file(path: &Path) -> Result<Palette, Box<dyn Error>> {
+    let format = PaletteFormat::from_path(path)?;
+    let bytes = fs::read(path)?;
+    let mut palette = match format {
+        PaletteFormat::Gpl => parse_gpl(&String::from_utf8(bytes)?)?,
+        PaletteFormat::Hex => parse_hex(&String::from_utf8(bytes)?)?,
+        PaletteFormat::Pal => parse_pal(&String::from_utf8(bytes)?)?,
+        PaletteFormat::Act => parse_act(&bytes)?,
+    };
+    //Only gpl files have a name, the others are named after the file
+    if palette.name.is_empty() {
+        if let Some(file_stem) = path.file_stem().and_then(|file_stem| file_stem.to_str()) {
+            palette.name = file_stem.to_string();
+        }
+    }
+    Ok(palette)
+}
+///Writes a palette in the format of the path's extension, the formats don't have alpha so it's dropped
+pub fn write_palette_file(path: &Path, palette: &Palette) -> Result<(), Box<dyn Error>> {
+    let bytes = match PaletteFormat::from_path(path)? {
+        PaletteFormat::Gpl => write_gpl(palette).into_bytes(),
+        PaletteFormat::Hex => write_hex(palette).into_bytes(),
+        PaletteFormat::Pal => write_pal(palette).into_bytes(),
+        PaletteFormat::Act => write_act(palette)?,
+    };
+    fs::write(path, bytes)?;
+    Ok(())
+}
+///Parses the start of a line as a color written as 3 numbers separated by whitespace, returns the rest of the line too
+fn parse_rgb_line(line: &str) -> Result<([u8; 4], String), Box<dyn Error>> {
+    let mut words = line.split_whitespace();
+    let mut color = [0, 0, 0, u8::MAX];
+    for channel in color.iter_mut().take(3) {
+        *channel = words
+            .next()
+            .ok_or_else(|| format!("the line {:?} doesn't have a whole color", line))?
+            .parse()?;
+    }
+    Ok((color, words.collect::<Vec<_>>().join(" ")))
+}
+pub fn parse_gpl(text: &str) -> Result<Palette, Box<dyn Error>> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("the file doesn't start with \"GIMP Palette\"".into());
+    }
+    let mut palette = Palette {
+        name: String::new(),
+        swatches: Vec::new(),
+    };
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("Name:") {
+            palette.name = name.trim().to_string();
+            continue;
+        }
+        let (color, name) = parse_rgb_line(line)?;
+        //GIMP names unnamed colors "Untitled"
+        let name = if name == "Untitled" {
+            String::new()
+        } else {
+            name
+        };
+        palette.swatches.push(Swatch { color, name });
+    }
+    Ok(palette)
+}
+pub fn write_gpl(palette: &Palette) -> String {
+    let mut text = format!("GIMP Palette\nName: {}\nColumns: 0\n#\n", palette.name);
+    for swatch in palette.swatches.iter() {
+        let [red, green, blue, _] = swatch.color;
+        let name = if swatch.name.is_empty() {
+            "Untitled"
+        } else {
+            &swatch.name
+        };
+        text.push_str(&format!("{:3} {:3} {:3}\t{}\n", red, green, blue, name));
+    }
+    text
+}
+pub fn parse_hex(text: &str) -> Result<Palette, Box<dyn Error>> {
+    let mut swatches = Vec::new();
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches('#');
+        if line.is_empty() {
+            continue;
+        }
+        if line.len() != 6 || !line.is_ascii() {
+            return Err(format!("{:?} isn't a RRGGBB color", line).into());
+        }
+        let mut color = [0, 0, 0, u8::MAX];
+        for (channel_index, channel) in color.iter_mut().take(3).enumerate() {
+            *channel = u8::from_str_radix(&line[channel_index * 2..channel_index * 2 + 2], 16)?;
+        }
+        swatches.push(Swatch {
+            color,
+            name: String::new(),
+        });
+    }
+    Ok(Palette {
+        name: String::new(),
+        swatches,
+    })
+}
+pub fn write_hex(palette: &Palette) -> String {
+    palette
+        .swatches
+        .iter()
+        .map(|swatch| {
+            let [red, green, blue, _] = swatch.color;
+            format!("{:02x}{:02x}{:02x}\n", red, green, blue)
+        })
+        .collect()
+}
+pub fn parse_pal(text: &str) -> Result<Palette, Box<dyn Error>> {
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some("JASC-PAL") {
+        return Err("the file doesn't start with \"JASC-PAL\"".into());
+    }
+    //The version, it's always 0100
+    lines.next();
+    let color_count: usize = lines
+        .next()
+        .ok_or("the file doesn't have a color count")?
+        .parse()?;
+    let mut swatches = Vec::with_capacity(color_count);
+    for line in lines.filter(|line| !line.is_empty()).take(color_count) {
+        let (color, _) = parse_rgb_line(line)?;
+        swatches.push(Swatch {
+            color,
+            name: String::new(),
+        });
+    }
+    if swatches.len() != color_count {
+        return Err(format!(
+            "the file should have {} colors but has {}",
+            color_count,
+            swatches.len()
+        )
+        .into());
+    }
+    Ok(Palette {
+        name: String::new(),
+        swatches,
+    })
+}
+pub fn write_pal(palette: &Palette) -> String {
+    let mut text = format!("JASC-PAL\r\n0100\r\n{}\r\n", palette.swatches.len());
+    for swatch in palette.swatches.iter() {
+        let [red, green, blue, _] = swatch.color;
+        text.push_str(&format!("{} {} {}\r\n", red, green, blue));
+    }
+    text
+}
+///The number of colors in an act file's table, even when it uses less of them
+const ACT_TABLE_LENGTH: usize = 256;
+pub fn parse_act(bytes: &[u8]) -> Result<Palette, Box<dyn Error>> {
+    if bytes.len() < ACT_TABLE_LENGTH * 3 {
+        return Err("the file is too short to have a color table".into());
+    }
+    //Newer files end with how many of the colors are used, followed by the transparent color's index
+    let color_count = if bytes.len() >= ACT_TABLE_LENGTH * 3 + 2 {
+        let count_bytes = [bytes[ACT_TABLE_LENGTH * 3], bytes[ACT_TABLE_LENGTH * 3 + 1]];
+        usize::min(u16::from_be_bytes(count_bytes) as usize, ACT_TABLE_LENGTH)
+    } else {
+        ACT_TABLE_LENGTH
+    };
+    let swatches = bytes[..color_count * 3]
+        .chunks_exact(3)
+        .map(|rgb| Swatch {
+            color: [rgb[0], rgb[1], rgb[2], u8::MAX],
+            name: String::new(),
+        })
+        .collect();
+    Ok(Palette {
+        name: String::new(),
+        swatches,
+    })
+}
+pub fn write_act(palette: &Palette) -> Result<Vec<u8>, Box<dyn Error>> {
+    if palette.swatches.len() > ACT_TABLE_LENGTH {
+        return Err(format!("act files can't have more than {} colors", ACT_TABLE_LENGTH).into());
+    }
+    let mut bytes = vec![0; ACT_TABLE_LENGTH * 3];
+    for (swatch, rgb) in palette.swatches.iter().zip(bytes.chunks_exact_mut(3)) {
+        rgb.copy_from_slice(&swatch.color[..3]);
+    }
+    bytes.extend_from_slice(&(palette.swatches.len() as u16).to_be_bytes());
+    //There's no transparent color
+    bytes.extend_from_slice(&u16::MAX.to_be_bytes());
+    Ok(bytes)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_palette() -> Palette {
+        Palette {
+            name: "Test".to_string(),
+            swatches: vec![
+                Swatch {
+                    color: [0, 0, 0, 255],
+                    name: "Black".to_string(),
+                },
+                Swatch {
+                    color: [255, 128, 7, 255],
+                    name: String::new(),
+                },
+                Swatch {
+                    color: [18, 52, 86, 255],
+                    name: "Dark blue".to_string(),
+                },
+            ],
+        }
+    }
+    ///The formats other than gpl don't keep names
+    fn without_names(palette: &Palette) -> Palette {
+        Palette {
+            name: String::new(),
+            swatches: palette
+                .swatches
+                .iter()
+                .map(|swatch| Swatch {
+                    color: swatch.color,
+                    name: String::new(),
+                })
+                .collect(),
+        }
+    }
+    #[test]
+    fn gpl_round_trip_keeps_names() {
+        let palette = test_palette();
+        assert_eq!(parse_gpl(&write_gpl(&palette)).unwrap(), palette);
+    }
+    #[test]
+    fn hex_round_trip() {
+        let palette = test_palette();
+        assert_eq!(
+            parse_hex(&write_hex(&palette)).unwrap(),
+            without_names(&palette)
+        );
+    }
+    #[test]
+    fn pal_round_trip() {
+        let palette = test_palette();
+        assert_eq!(
+            parse_pal(&write_pal(&palette)).unwrap(),
+            without_names(&palette)
+        );
+    }
+    #[test]
+    fn act_round_trip() {
+        let palette = test_palette();
+        let bytes = write_act(&palette).unwrap();
+        assert_eq!(bytes.len(), ACT_TABLE_LENGTH * 3 + 4);
+        assert_eq!(parse_act(&bytes).unwrap(), without_names(&palette));
+    }
+    #[test]
+    fn file_round_trip_names_palettes_after_the_file() {
+        let path = std::env::temp_dir().join("sprite_editor_palette_test.hex");
+        let palette = test_palette();
+        write_palette_file(&path, &palette).unwrap();
+        let read_palette = read_palette_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read_palette.name, "sprite_editor_palette_test");
+        assert_eq!(read_palette.swatches, without_names(&palette).swatches);
+    }
+    #[test]
+    fn writing_drops_alpha() {
+        let palette = Palette {
+            name: String::new(),
+            swatches: vec![Swatch {
+                color: [1, 2, 3, 4],
+                name: String::new(),
+            }],
+        };
+        assert_eq!(
+            parse_hex(&write_hex(&palette)).unwrap().swatches[0].color,
+            [1, 2, 3, 255]
+        );
+    }
+    #[test]
+    fn unknown_extensions_are_rejected() {
+        assert!(PaletteFormat::from_path(Path::new("palette.png")).is_err());
+        assert!(PaletteFormat::from_path(Path::new("palette")).is_err());
+        assert_eq!(
+            PaletteFormat::from_path(Path::new("palette.GPL")).unwrap(),
+            PaletteFormat::Gpl
+        );
+    }
+    #[test]
+    fn malformed_gpl_is_rejected() {
+        assert!(parse_gpl("Not A Palette\n0 0 0\tBlack\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n0 0\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n0 0 red\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n0 0 256\n").is_err());
+    }
+    #[test]
+    fn malformed_hex_is_rejected() {
+        assert!(parse_hex("12345\n").is_err());
+        assert!(parse_hex("1234567\n").is_err());
+        assert!(parse_hex("12345g\n").is_err());
+        assert!(parse_hex("ééé\n").is_err());
+    }
+    #[test]
+    fn malformed_pal_is_rejected() {
+        assert!(parse_pal("JASC\r\n0100\r\n1\r\n0 0 0\r\n").is_err());
+        assert!(parse_pal("JASC-PAL\r\n0100\r\n").is_err());
+        assert!(parse_pal("JASC-PAL\r\n0100\r\ntwo\r\n0 0 0\r\n").is_err());
+        assert!(parse_pal("JASC-PAL\r\n0100\r\n2\r\n0 0 0\r\n").is_err());
+    }
+    #[test]
+    fn malformed_act_is_rejected() {
+        assert!(parse_act(&[0; ACT_TABLE_LENGTH * 3 - 1]).is_err());
+        let too_many_colors = Palette {
+            name: String::new(),
+            swatches: vec![
+                Swatch {
+                    color: [0; 4],
+                    name: String::new(),
+                };
+                ACT_TABLE_LENGTH + 1
+            ],
+        };
+        assert!(write_act(&too_many_colors).is_err());
+    }
+    #[test]
+    fn act_without_a_color_count_has_every_color() {
+        assert_eq!(
+            parse_act(&[0; ACT_TABLE_LENGTH * 3])
+                .unwrap()
+                .swatches
+                .len(),
+            ACT_TABLE_LENGTH
+        );
+        //Counts bigger than the table are clamped
+        let mut bytes = vec![0; ACT_TABLE_LENGTH * 3];
+        bytes.extend_from_slice(&1000u16.to_be_bytes());
+        assert_eq!(parse_act(&bytes).unwrap().swatches.len(), ACT_TABLE_LENGTH);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::data::{
+    animations::TileSetAnimations,
+    events::{OpenProjectEvent, SaveProjectEvent},
+    layers::{LayerPixels, TileLayers, TileSetLayers},
+    project::{ProjectDocument, TileDocument, TileSetDocument, TileSetViewDocument},
+    resources::{ProjectPath, TileSetOrderCounter},
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TileIndices, TileName, TilePosition},
+    tileset_entity::{
+        LoadedTile, LoadedTiles, NewlySelected, TileSetBundle, TileSetName, TileSetOrder,
+        TileSetPalette, TileSetSettings, TileSetView,
+    },
+};
+use bevy::prelude::*;
+use bevy_common::input::marker_components::MainCamera;
+use ron::ser::PrettyConfig;
+use std::{error::Error, fs, path::Path};
+
+///Saves every open [TileSetBundle](TileSetBundle) to a project file
+pub fn save_project(
+    mut save_project_events: EventReader<SaveProjectEvent>,
+    mut project_path: ResMut<ProjectPath>,
+    tileset_query: Query<(
+        &TileSetOrder,
+        &TileSetName,
+        &TileSetSettings,
+        &TileSetView,
+        &Children,
+        Option<&CurrentlySelected>,
+        Option<&TileSetPalette>,
+        &TileSetLayers,
+        &TileSetAnimations,
+    )>,
+    tile_query: Query<(
+        &TileName,
+        &TilePosition,
+        &TileData,
+        &TileLayers,
+        Option<&TileIndices>,
+    )>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    for event in save_project_events.iter() {
+        let mut tilesets: Vec<_> = tileset_query.iter().collect();
+        //Keep the tilesets in the order they were opened in, entity ids are reused after despawning so they can't be used for this
+        tilesets.sort_by_key(|(order, ..)| **order);
+        let mut document = ProjectDocument::default();
+        for (
+            index,
+            (
+                _order,
+                name,
+                settings,
+                view,
+                children,
+                currently_selected,
+                tileset_palette,
+                tileset_layers,
+                tileset_animations,
+            ),
+        ) in tilesets.into_iter().enumerate()
+        {
+            //The selected tileset's view is only updated when switching away from it, the camera has the up to date one
+            let camera_transform = match (currently_selected, camera_query.single()) {
+                (Some(_), Ok(camera_transform)) => {
+                    document.selected_tileset = Some(index);
+                    *camera_transform
+                }
+                _ => view.camera_transform,
+            };
+            let mut tiles: Vec<TileDocument> = children
+                .iter()
+                .filter_map(|child| tile_query.get(*child).ok())
+                .map(
+                    |(tile_name, tile_position, tile_data, tile_layers, tile_indices)| {
+                        let layer_data = |layer| {
+                            tile_layers
+                                .layer_data(layer, tileset_layers, tile_data)
+                                .to_vec()
+                        };
+                        //Only the tiles of indexed tilesets have indices
+                        let layer_indices = |layer| {
+                            tile_indices.map_or(Vec::new(), |tile_indices| {
+                                tile_layers
+                                    .layer_indices(layer, tileset_layers, tile_indices)
+                                    .to_vec()
+                            })
+                        };
+                        TileDocument {
+                            name: tile_name.name.clone(),
+                            position: [tile_position.position.x, tile_position.position.y],
+                            data: layer_data(0),
+                            layers: (1..tileset_layers.layers.len()).map(layer_data).collect(),
+                            indices: layer_indices(0),
+                            layer_indices: (1..tileset_layers.layers.len())
+                                .map(layer_indices)
+                                .collect(),
+                        }
+                    },
+                )
+                .collect();
+            tiles.sort_by_key(|tile| (tile.position[1], tile.position[0]));
+            document.tilesets.push(TileSetDocument {
+                name: name.name.clone(),
+                settings: *settings,
+                view: TileSetViewDocument {
+                    camera_translation: camera_transform.translation.into(),
+                    camera_scale: camera_transform.scale.into(),
+                },
+                tiles,
+                palette: tileset_palette.map(|tileset_palette| tileset_palette.palette.clone()),
+                layers: tileset_layers.layers.clone(),
+                animations: tileset_animations.animations.clone(),
+            });
+        }
+        match write_project_file(&event.path, &document) {
+            Ok(()) => project_path.path = Some(event.path.clone()),
+            Err(error) => error!("Couldn't save the project to {:?}: {}", event.path, error),
+        }
+    }
+}
+///Replaces the open [TileSetBundle](TileSetBundle)s with the ones in a project file
+pub fn open_project(
+    mut commands: Commands,
+    mut open_project_events: EventReader<OpenProjectEvent>,
+    mut project_path: ResMut<ProjectPath>,
+    mut tileset_order_counter: ResMut<TileSetOrderCounter>,
+    tileset_query: Query<Entity, With<TileSetSettings>>,
+) {
+    for event in open_project_events.iter() {
+        let document = match read_project_file(&event.path) {
+            Ok(document) => document,
+            Err(error) => {
+                error!("Couldn't open the project {:?}: {}", event.path, error);
+                continue;
+            }
+        };
+        for tileset_entity in tileset_query.iter() {
+            commands.entity(tileset_entity).despawn_recursive();
+        }
+        let selected_tileset = document.selected_tileset.unwrap_or(0);
+        for (index, tileset_document) in document.tilesets.into_iter().enumerate() {
+            let settings = tileset_document.settings;
+            let tile_data_length = settings.tile_width * settings.tile_height * 4;
+            //The bottom layer starts as the active one, so it's pixels are in the tiles' data
+            let layer_count = usize::max(tileset_document.layers.len(), 1);
+            //Missing layers are blank
+            let get_tile_layers = |mut upper_layers: std::vec::IntoIter<Vec<u8>>| TileLayers {
+                layers: std::iter::once(LayerPixels::default())
+                    .chain((1..layer_count).map(|_| {
+                        let mut layer_data = upper_layers.next().unwrap_or_default();
+                        if layer_data.len() != tile_data_length {
+                            layer_data = Vec::new();
+                        }
+                        LayerPixels {
+                            data: layer_data,
+                            indices: Vec::new(),
+                        }
+                    }))
+                    .collect(),
+            };
+            let mut tiles: Vec<LoadedTile> = (0..settings.tileset_width * settings.tileset_height)
+                .map(|_| LoadedTile {
+                    layers: get_tile_layers(Vec::new().into_iter()),
+                    ..Default::default()
+                })
+                .collect();
+            for tile_document in tileset_document.tiles {
+                let [x, y] = tile_document.position;
+                if x as usize >= settings.tileset_width || y as usize >= settings.tileset_height {
+                    continue;
+                }
+                let mut data = tile_document.data;
+                //Tiles with the wrong amount of data are initiated as blank ones instead
+                if data.len() != tile_data_length {
+                    data.clear();
+                }
+                let mut layers = get_tile_layers(tile_document.layers.into_iter());
+                let indices = tileset_document.palette.as_ref().and_then(|_| {
+                    get_loaded_indices(
+                        &data,
+                        tile_document.indices,
+                        &mut layers,
+                        tile_document.layer_indices,
+                    )
+                });
+                tiles[y as usize * settings.tileset_width + x as usize] = LoadedTile {
+                    name: TileName {
+                        name: tile_document.name,
+                    },
+                    data: TileData { data },
+                    layers,
+                    indices,
+                };
+            }
+            //The tilesets are opened in the order they were saved in
+            let mut tileset = commands.spawn_bundle(TileSetBundle::with_name(
+                settings,
+                tileset_document.name,
+                tileset_order_counter.next(),
+            ));
+            tileset.insert(LoadedTiles { tiles }).insert(TileSetView {
+                camera_transform: Transform {
+                    translation: tileset_document.view.camera_translation.into(),
+                    scale: tileset_document.view.camera_scale.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            if let Some(palette) = tileset_document.palette {
+                tileset.insert(TileSetPalette { palette });
+            }
+            tileset.insert(TileSetAnimations {
+                animations: tileset_document.animations,
+            });
+            if !tileset_document.layers.is_empty() {
+                tileset.insert(TileSetLayers {
+                    layers: tileset_document.layers,
+                    active_layer: 0,
+                });
+            }
+            //Only one tileset can be newly selected
+            if index != selected_tileset {
+                tileset.remove::<NewlySelected>();
+            }
+        }
+        project_path.path = Some(event.path.clone());
+    }
+}
+///Returns the saved indices of an indexed tile and puts the upper layers' ones in their [LayerPixels](LayerPixels)
+///
+///Projects saved before the indices were, or with indices that don't match the pixels, get None, the tile is indexed from it's colors instead
+fn get_loaded_indices(
+    data: &[u8],
+    indices: Vec<u8>,
+    tile_layers: &mut TileLayers,
+    layer_indices: Vec<Vec<u8>>,
+) -> Option<TileIndices> {
+    //Blank layers have no indices either
+    let matches_pixels = |indices: &[u8], data: &[u8]| indices.len() * 4 == data.len();
+    let upper_layers = &mut tile_layers.layers[1..];
+    if !matches_pixels(&indices, data)
+        || upper_layers.len() != layer_indices.len()
+        || upper_layers
+            .iter()
+            .zip(layer_indices.iter())
+            .any(|(layer_pixels, indices)| !matches_pixels(indices, &layer_pixels.data))
+    {
+        return None;
+    }
+    for (layer_pixels, indices) in upper_layers.iter_mut().zip(layer_indices) {
+        layer_pixels.indices = indices;
+    }
+    Some(TileIndices { indices })
+}
+///Writes a [ProjectDocument](ProjectDocument) to disk as ron
+pub fn write_project_file(path: &Path, document: &ProjectDocument) -> Result<(), Box<dyn Error>> {
+    //Every tile gets a line of it's own instead of every byte getting one
+    let ron = ron::ser::to_string_pretty(document, PrettyConfig::new().with_depth_limit(4))?;
+    fs::write(path, ron)?;
+    Ok(())
+}
+///Reads a [ProjectDocument](ProjectDocument) written by [write_project_file](write_project_file)
+pub fn read_project_file(path: &Path) -> Result<ProjectDocument, Box<dyn Error>> {
+    let ron = fs::read_to_string(path)?;
+    Ok(ron::de::from_str(&ron)?)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        layers::LayerSettings,
+        palette::{Palette, Swatch},
+    };
+
+    fn red_swatch(name: &str) -> Swatch {
+        Swatch {
+            color: [255, 0, 0, 255],
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn indices_of_duplicate_palette_colors_survive_a_round_trip() {
+        let path = std::env::temp_dir().join("sprite_editor_project_indices_test.ron");
+        //Both swatches are red, the second one's index can't be found again from the color
+        let palette = Palette {
+            name: "Duplicates".to_string(),
+            swatches: vec![red_swatch("Red"), red_swatch("Also red")],
+        };
+        let document = ProjectDocument {
+            tilesets: vec![TileSetDocument {
+                name: "Indexed".to_string(),
+                settings: TileSetSettings {
+                    tile_width: 2,
+                    tile_height: 1,
+                    tileset_width: 1,
+                    tileset_height: 1,
+                },
+                view: TileSetViewDocument {
+                    camera_translation: [0.0; 3],
+                    camera_scale: [1.0; 3],
+                },
+                tiles: vec![TileDocument {
+                    name: String::new(),
+                    position: [0, 0],
+                    data: [[255, 0, 0, 255]; 2].concat(),
+                    layers: vec![[255, 0, 0, 255, 0, 0, 0, 0].to_vec()],
+                    indices: vec![1, 0],
+                    layer_indices: vec![vec![1, 0]],
+                }],
+                palette: Some(palette),
+                layers: vec![
+                    LayerSettings::new("Layer 1".to_string()),
+                    LayerSettings::new("Layer 2".to_string()),
+                ],
+                animations: Vec::new(),
+            }],
+            selected_tileset: Some(0),
+        };
+        write_project_file(&path, &document).unwrap();
+        let mut read_document = read_project_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        let tile_document = read_document.tilesets[0].tiles.remove(0);
+        assert_eq!(tile_document.indices, vec![1, 0]);
+        assert_eq!(tile_document.layer_indices, vec![vec![1, 0]]);
+
+        let mut tile_layers = TileLayers {
+            layers: vec![
+                LayerPixels::default(),
+                LayerPixels {
+                    data: tile_document.layers[0].clone(),
+                    indices: Vec::new(),
+                },
+            ],
+        };
+        let tile_indices = get_loaded_indices(
+            &tile_document.data,
+            tile_document.indices,
+            &mut tile_layers,
+            tile_document.layer_indices,
+        )
+        .unwrap();
+        assert_eq!(tile_indices.indices, vec![1, 0]);
+        assert_eq!(tile_layers.layers[1].indices, vec![1, 0]);
+    }
+
+    #[test]
+    fn indices_that_dont_match_the_pixels_are_found_again() {
+        let data = [[255, 0, 0, 255]; 2].concat();
+        let mut tile_layers = TileLayers::default();
+        //Projects saved before the indices were have none
+        assert!(get_loaded_indices(&data, Vec::new(), &mut tile_layers, Vec::new()).is_none());
+        assert!(get_loaded_indices(&data, vec![0], &mut tile_layers, Vec::new()).is_none());
+        //Every upper layer needs it's indices too
+        let mut tile_layers = TileLayers {
+            layers: vec![
+                LayerPixels::default(),
+                LayerPixels {
+                    data: data.clone(),
+                    indices: Vec::new(),
+                },
+            ],
+        };
+        assert!(get_loaded_indices(&data, vec![0, 0], &mut tile_layers, Vec::new()).is_none());
+        assert!(get_loaded_indices(&data, vec![0, 0], &mut tile_layers, vec![vec![0]]).is_none());
+        //Blank tiles have no indices
+        let mut tile_layers = TileLayers::default();
+        let tile_indices = get_loaded_indices(&[], Vec::new(), &mut tile_layers, Vec::new());
+        assert_eq!(tile_indices.unwrap().indices, Vec::<u8>::new());
+    }
+
+    ///Spawns a tileset without tiles, with the components [save_project](save_project) needs
+    fn spawn_empty_tileset(world: &mut World, name: &str) -> Entity {
+        let order = world
+            .get_resource_mut::<TileSetOrderCounter>()
+            .unwrap()
+            .next();
+        world
+            .spawn()
+            .insert_bundle(TileSetBundle::with_name(
+                TileSetSettings::default(),
+                name.to_string(),
+                order,
+            ))
+            .insert(Children::with(&[]))
+            .id()
+    }
+
+    fn get_saved_names(
+        world: &mut World,
+        save_stage: &mut SystemStage,
+        path: &Path,
+    ) -> Vec<String> {
+        world
+            .get_resource_mut::<Events<SaveProjectEvent>>()
+            .unwrap()
+            .send(SaveProjectEvent {
+                path: path.to_path_buf(),
+            });
+        save_stage.run(world);
+        let document = read_project_file(path).unwrap();
+        document
+            .tilesets
+            .into_iter()
+            .map(|tileset_document| tileset_document.name)
+            .collect()
+    }
+
+    #[test]
+    fn tilesets_keep_their_order_after_one_is_despawned() {
+        let path = std::env::temp_dir().join("sprite_editor_project_order_test.ron");
+        let mut world = World::default();
+        world.insert_resource(ProjectPath::default());
+        world.insert_resource(TileSetOrderCounter::default());
+        world.insert_resource(Events::<SaveProjectEvent>::default());
+        world.insert_resource(Events::<OpenProjectEvent>::default());
+        let mut save_stage = SystemStage::single_threaded().with_system(save_project.system());
+        let mut open_stage = SystemStage::single_threaded().with_system(open_project.system());
+
+        let first = spawn_empty_tileset(&mut world, "First");
+        spawn_empty_tileset(&mut world, "Second");
+        spawn_empty_tileset(&mut world, "Third");
+        world.despawn(first);
+        //The new tileset gets the despawned one's id
+        let fourth = spawn_empty_tileset(&mut world, "Fourth");
+        assert_eq!(fourth.id(), first.id());
+        let expected_names = vec!["Second", "Third", "Fourth"];
+        assert_eq!(
+            get_saved_names(&mut world, &mut save_stage, &path),
+            expected_names
+        );
+
+        world
+            .get_resource_mut::<Events<OpenProjectEvent>>()
+            .unwrap()
+            .send(OpenProjectEvent { path: path.clone() });
+        open_stage.run(&mut world);
+        //The tiles would be spawned as the children when the opened tilesets are initiated
+        let opened_tilesets: Vec<Entity> = world
+            .query_filtered::<Entity, With<TileSetName>>()
+            .iter(&world)
+            .collect();
+        assert_eq!(opened_tilesets.len(), 3);
+        for tileset_entity in opened_tilesets {
+            world.entity_mut(tileset_entity).insert(Children::with(&[]));
+        }
+        let saved_again_names = get_saved_names(&mut world, &mut save_stage, &path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(saved_again_names, expected_names);
+    }
+}
@@ -1,20 +1,34 @@
 use crate::data::{
+    chunks::{TileSetChunk, TileSetChunkMaterial, TileSetChunks},
+    grid::{GridSettings, TileSetGrid, TileSetGridMaterial},
+    resources::BlankChunkMaterials,
     shared_components::Uninitiated,
-    tile_entity::{TileBundle, TileData, TilePosition, TileSettings},
-    tileset_entity::{TileSetSettings, TileSetView},
+    tile_entity::{free_transparent_pixels, TileBundle, TilePosition, TileSettings},
+    tileset_entity::{LoadedTiles, TileSetSettings, TileSetTiles, TileSetView},
 };
-use bevy::utils::HashMap;
-use bevy::{prelude::*, tasks::ComputeTaskPool};
+use bevy::{prelude::*, render::pipeline::RenderPipeline};
 use bevy_common::input::data_components::CameraZoomLimit;
-///Initiates a newly Created [TileSetBundle](TileSetBundle) entity and it's [TileBundle](TileBundle) children
+///Initiates a newly Created [TileSetBundle](TileSetBundle) entity and it's [TileBundle](TileBundle), [TileSetChunk](TileSetChunk) and [TileSetGrid](TileSetGrid) children
 pub fn init_tileset(
     mut commands: Commands,
-    mut query: Query<(Entity, &TileSetSettings, &mut TileSetView), With<Uninitiated>>,
+    mut query: Query<
+        (
+            Entity,
+            &TileSetSettings,
+            &mut TileSetView,
+            Option<&mut LoadedTiles>,
+        ),
+        With<Uninitiated>,
+    >,
     mut textures: ResMut<Assets<Texture>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut materials: ResMut<Assets<TileSetChunkMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut blank_chunk_materials: ResMut<BlankChunkMaterials>,
+    mut grid_materials: ResMut<Assets<TileSetGridMaterial>>,
+    grid_settings: Res<GridSettings>,
     windows: Res<Windows>,
 ) {
-    for (tileset_entity, tileset_settings, mut tileset_view) in query.iter_mut() {
+    for (tileset_entity, tileset_settings, mut tileset_view, mut loaded_tiles) in query.iter_mut() {
         //This should probably always succede but
         let window = windows.get_primary().unwrap();
         //Get The scale to fit the tileset to screen
@@ -28,8 +42,23 @@ pub fn init_tileset(
             max_zoom: Vec3::new(scale / 10.0, scale / 10.0, 1.0),
             min_zoom: Vec3::new(1.0, 1.0, 1.0),
         };
-        //TODO: Disable 1 pixel sprite creation
-        let texture = Texture::default();
+        //The tileset is drawn in a few big chunks instead of a sprite for each tile
+        let chunk_size_in_tiles = TileSetChunks::get_chunk_size_in_tiles(tileset_settings);
+        let chunk_columns = (tileset_settings.tileset_width as u32 + chunk_size_in_tiles.x - 1)
+            / chunk_size_in_tiles.x;
+        let chunk_rows = (tileset_settings.tileset_height as u32 + chunk_size_in_tiles.y - 1)
+            / chunk_size_in_tiles.y;
+        let tile_size = UVec2::new(
+            tileset_settings.tile_width as u32,
+            tileset_settings.tile_height as u32,
+        );
+        let tileset_size = get_total_tileset_size_pixels(tileset_settings);
+        let mut tileset_chunks = TileSetChunks {
+            chunk_size_in_tiles,
+            columns: chunk_columns,
+            chunks: Vec::new(),
+        };
+        let mut tileset_tiles = TileSetTiles::default();
         commands
             .entity(tileset_entity)
             .insert(Transform {
@@ -38,11 +67,74 @@ pub fn init_tileset(
                 ..Default::default()
             })
             .with_children(|tileset_parent| {
+                for y_chunk in 0..chunk_rows {
+                    for x_chunk in 0..chunk_columns {
+                        let min_tile = UVec2::new(x_chunk, y_chunk) * chunk_size_in_tiles;
+                        //The chunks at the top and right of the tileset get the tiles that are left
+                        let size_in_tiles = chunk_size_in_tiles.min(
+                            UVec2::new(
+                                tileset_settings.tileset_width as u32,
+                                tileset_settings.tileset_height as u32,
+                            ) - min_tile,
+                        );
+                        let chunk_size = size_in_tiles * tile_size;
+                        let chunk_center = (min_tile * tile_size).as_f32()
+                            + chunk_size.as_f32() / 2.0
+                            - tileset_size / 2.0;
+                        //Every blank chunk of this size shows the same material, until something is drawn in it
+                        let material_handle = blank_chunk_materials.get_or_add(
+                            chunk_size,
+                            &mut textures,
+                            &mut materials,
+                        );
+                        let chunk_entity = tileset_parent
+                            .spawn_bundle(MeshBundle {
+                                mesh: meshes.add(Mesh::from(shape::Quad::new(chunk_size.as_f32()))),
+                                render_pipelines: RenderPipelines::from_pipelines(vec![
+                                    RenderPipeline::new(
+                                        crate::TILESET_CHUNK_PIPELINE_HANDLE.typed(),
+                                    ),
+                                ]),
+                                //Chunks are shown when their tileset gets selected, several tilesets can be spawned together when opening a project
+                                visible: Visible {
+                                    is_visible: false,
+                                    is_transparent: true,
+                                },
+                                transform: Transform::from_translation(chunk_center.extend(0.0)),
+                                ..Default::default()
+                            })
+                            .insert(TileSetChunk {
+                                min_tile,
+                                size_in_tiles,
+                            })
+                            .insert(material_handle)
+                            .id();
+                        tileset_chunks.chunks.push(chunk_entity);
+                    }
+                }
+                //The grids are drawn over the whole tileset, above the chunks and the onion skin
+                tileset_parent
+                    .spawn_bundle(MeshBundle {
+                        mesh: meshes.add(Mesh::from(shape::Quad::new(tileset_size))),
+                        render_pipelines: RenderPipelines::from_pipelines(vec![
+                            RenderPipeline::new(crate::TILESET_GRID_PIPELINE_HANDLE.typed()),
+                        ]),
+                        visible: Visible {
+                            is_visible: false,
+                            is_transparent: true,
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                        ..Default::default()
+                    })
+                    .insert(TileSetGrid)
+                    .insert(grid_materials.add(TileSetGridMaterial::new(
+                        tile_size.as_f32(),
+                        tileset_size,
+                        &grid_settings,
+                    )));
                 for y_tileset in 0..tileset_settings.tileset_height {
                     for x_tileset in 0..tileset_settings.tileset_width {
-                        let texture_handle = textures.add(texture.clone());
-                        let material_handle = materials.add(ColorMaterial::texture(texture_handle));
-                        tileset_parent.spawn_bundle(TileBundle::new(
+                        let mut tile_bundle = TileBundle::new(
                             TileSettings {
                                 tile_width: tileset_settings.tile_width,
                                 tile_height: tileset_settings.tile_height,
@@ -50,14 +142,13 @@ pub fn init_tileset(
                             TilePosition {
                                 position: UVec2::new(x_tileset as u32, y_tileset as u32),
                             },
-                            material_handle,
                             Transform {
                                 //scale,
                                 translation: Vec3::new(
                                     (x_tileset as f32
                                         - tileset_settings.tileset_width as f32 / 2.0)
                                         * tileset_settings.tile_width as f32
-                                        + tileset_settings.tile_height as f32 / 2.0,
+                                        + tileset_settings.tile_width as f32 / 2.0,
                                     (y_tileset as f32
                                         - tileset_settings.tileset_height as f32 / 2.0)
                                         * tileset_settings.tile_height as f32
@@ -66,12 +157,49 @@ pub fn init_tileset(
                                 ),
                                 ..Default::default()
                             },
-                        ));
+                        );
+                        let mut tile_indices = None;
+                        //If the tileset was loaded from a file, the tile starts with the loaded data instead of being transparent
+                        if let Some(loaded_tiles) = loaded_tiles.as_mut() {
+                            if let Some(loaded_tile) = loaded_tiles
+                                .tiles
+                                .get_mut(y_tileset * tileset_settings.tileset_width + x_tileset)
+                            {
+                                tile_bundle.name = std::mem::take(&mut loaded_tile.name);
+                                tile_bundle.data = std::mem::take(&mut loaded_tile.data);
+                                tile_bundle.layers = std::mem::take(&mut loaded_tile.layers);
+                                tile_indices = loaded_tile.indices.take();
+                                //Transparent tiles and layers stay blank, so they don't keep their pixels around
+                                free_transparent_pixels(&mut tile_bundle.data.data);
+                                if let Some(tile_indices) = tile_indices.as_mut() {
+                                    if tile_bundle.data.is_blank() {
+                                        tile_indices.indices.clear();
+                                    }
+                                }
+                                for layer_pixels in tile_bundle.layers.layers.iter_mut() {
+                                    free_transparent_pixels(&mut layer_pixels.data);
+                                    if layer_pixels.data.is_empty() {
+                                        layer_pixels.indices.clear();
+                                    }
+                                }
+                            }
+                        }
+                        let mut tile = tileset_parent.spawn_bundle(tile_bundle);
+                        //Indexed tiles that were saved with their indices don't need to be indexed again
+                        if let Some(tile_indices) = tile_indices {
+                            tile.insert(tile_indices);
+                        }
+                        tileset_tiles.tiles.push(tile.id());
                     }
                 }
             });
         //Don't forget to remove the marker component so this function won't run for it again if another tileset is created
-        commands.entity(tileset_entity).remove::<Uninitiated>();
+        commands
+            .entity(tileset_entity)
+            .insert(tileset_chunks)
+            .insert(tileset_tiles)
+            .remove::<Uninitiated>()
+            .remove::<LoadedTiles>();
     }
 }
 ///Calculates the total size of the [TileSetBundle](TileSetBundle)
@@ -96,72 +224,15 @@ pub fn get_scale_fit_tileset_to_screen(
     }
     1.0 / percent
 }
-///FIXME: When this commit: https://github.com/bevyengine/bevy/pull/1945 is merged, change to this instead of init_tile_seq?
+///This initiates a newly created [TileBundle](TileBundle)
 ///
-///This initiates a newly created [TileBundle](TileBundle) in a parralel manner
-#[allow(dead_code)]
-pub fn init_tile_par(
-    mut commands: Commands,
-    pool: Res<ComputeTaskPool>,
-    mut query: Query<(Entity, &TileSettings, &mut TileData), With<Uninitiated>>,
-) {
-    query.par_for_each_mut(&pool, 100, |(_entity, tile_settings, mut tile_data)| {
-        //Creating a transparent texture once
-        let mut sprite_data: Vec<u8> =
-            Vec::with_capacity((tile_settings.tile_width * tile_settings.tile_height * 4) as usize);
-        //Going in reverse because in textures y is higher at the bottom
-        for _y_tile in (0..tile_settings.tile_height).rev() {
-            for _x_tile in 0..tile_settings.tile_width {
-                sprite_data.push(0);
-                sprite_data.push(0);
-                sprite_data.push(0);
-                sprite_data.push(0);
-            }
-        }
-        tile_data.data = sprite_data;
-    });
-
-    //Remove the Unitiated component from the entity
-    for (entity, _, _) in query.iter_mut() {
-        commands.entity(entity).remove::<Uninitiated>();
-    }
-}
-///This initiates a newly created [TileBundle](TileBundle) in a sequential manner
+///Tiles start blank, their pixels are only allocated when something is drawn on them
 pub fn init_tile_seq(
     mut commands: Commands,
-    mut query: Query<(Entity, &TileSettings, &mut TileData), With<Uninitiated>>,
+    query: Query<Entity, (With<TileSettings>, With<Uninitiated>)>,
 ) {
-    //Honestly, there shouldn't be two sets of new tiles in the same frame but to be safe
-    //Also, this could belong in a Local, but i don't even know if it'll be a perf improvement
-    let mut hm: HashMap<(usize, usize), Vec<u8>> = HashMap::default();
-
-    for (entity, tile_settings, mut tile_data/*, mut visible*/) in query.iter_mut() {
-        if let Some(texture_data) = hm.get(&(tile_settings.tile_width, tile_settings.tile_height)) {
-            //Cloning existing texture data for tile_settings we already encountered
-            tile_data.data = texture_data.clone();
-        } else {
-            //Creating a transparent texture for these newly encountered settings
-            let mut texture_data = Vec::<u8>::with_capacity(
-                (tile_settings.tile_width * tile_settings.tile_height * 4) as usize,
-            );
-            //TODO: This could be a one dimensional loop, maybe replace it after you copied into the pencil mechanism
-            for _y_tile in (0..tile_settings.tile_height).rev() {
-                for _x_tile in 0..tile_settings.tile_width {
-                    texture_data.push(0);
-                    texture_data.push(0);
-                    texture_data.push(0);
-                    texture_data.push(0);
-                }
-            }
-            //Insert this data to the hash map so we could just clone it instead of creating a new vec
-            hm.insert(
-                (tile_settings.tile_width, tile_settings.tile_height),
-                texture_data.clone(),
-            );
-            //Don't forget to set the tile's data
-            tile_data.data = texture_data;
-        }
-        //Remove the marker so the data won't be deleted if we decide to create a new Tileset later
+    for entity in query.iter() {
+        //Remove the marker so the tile won't be initiated again if we decide to create a new Tileset later
         commands.entity(entity).remove::<Uninitiated>();
     }
 }
@@ -1,2 +1,5 @@
+pub mod animating;
+pub mod file_handling;
 pub mod initializing;
+pub mod texture_uploading;
 pub mod tileset_editing;
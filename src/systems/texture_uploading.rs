@@ -0,0 +1,81 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_graph::{base, Node, RenderGraph, ResourceSlots},
+        renderer::{BufferInfo, BufferUsage, RenderContext},
+        texture::{Extent3d, TEXTURE_ASSET_INDEX},
+    },
+};
+
+use crate::data::resources::{TextureUpload, TextureUploads};
+///The name of the [TextureUploadNode](TextureUploadNode) in the render graph
+pub const TEXTURE_UPLOAD_NODE: &str = "tileset_chunk_texture_upload";
+///Copies the changed rectangles of tiles straight into the [TileSetChunk](crate::data::chunks::TileSetChunk) textures that are already on the gpu
+///
+///Modifying a [Texture](Texture) asset makes bevy recreate it and upload all of it's pixels, this only uploads what changed.
+///The texture asset keeps the pixels it was created with, each tile's [TileTexture](crate::data::tile_entity::TileTexture) has the ones that are shown
+#[derive(Debug, Default)]
+pub struct TextureUploadNode {
+    uploads: Vec<TextureUpload>,
+}
+impl Node for TextureUploadNode {
+    fn prepare(&mut self, world: &mut World) {
+        if let Some(mut texture_uploads) = world.get_resource_mut::<TextureUploads>() {
+            self.uploads.append(&mut texture_uploads.uploads);
+        }
+    }
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        for upload in self.uploads.drain(..) {
+            //The texture could have been removed since the upload was queued
+            let texture_id = match render_context
+                .resources()
+                .get_asset_resource(&upload.texture_handle, TEXTURE_ASSET_INDEX)
+                .and_then(|texture_resource| texture_resource.get_texture())
+            {
+                Some(texture_id) => texture_id,
+                None => continue,
+            };
+            //The gpu wants every row of the buffer to start at an aligned offset
+            let width = upload.size.x as usize;
+            let aligned_width = render_context.resources().get_aligned_texture_size(width);
+            let mut aligned_pixels = vec![0; aligned_width * upload.size.y as usize * 4];
+            for (row_index, row) in upload.pixels.chunks_exact(width * 4).enumerate() {
+                let offset = row_index * aligned_width * 4;
+                aligned_pixels[offset..offset + width * 4].copy_from_slice(row);
+            }
+            let buffer = render_context.resources().create_buffer_with_data(
+                BufferInfo {
+                    buffer_usage: BufferUsage::COPY_SRC,
+                    ..Default::default()
+                },
+                &aligned_pixels,
+            );
+            render_context.copy_buffer_to_texture(
+                buffer,
+                0,
+                (aligned_width * 4) as u32,
+                texture_id,
+                [upload.origin.x, upload.origin.y, 0],
+                0,
+                Extent3d::new(upload.size.x, upload.size.y, 1),
+            );
+            render_context.resources().remove_buffer(buffer);
+        }
+    }
+}
+///Adds the [TextureUploadNode](TextureUploadNode) to the render graph, after bevy's own texture copying and before the main pass
+pub fn setup_texture_upload_node(mut render_graph: ResMut<RenderGraph>) {
+    render_graph.add_node(TEXTURE_UPLOAD_NODE, TextureUploadNode::default());
+    render_graph
+        .add_node_edge(base::node::TEXTURE_COPY, TEXTURE_UPLOAD_NODE)
+        .unwrap();
+    render_graph
+        .add_node_edge(TEXTURE_UPLOAD_NODE, base::node::MAIN_PASS)
+        .unwrap();
+}
@@ -0,0 +1,139 @@
+use crate::{
+    data::{
+        events::HistoryEvent,
+        history::{CurrentStroke, HistoryEntry, TileChange, TileSetHistory},
+        layers::{TileLayers, TileSetLayers},
+        resources::SelectedTool,
+        shared_components::CurrentlySelected,
+        tile_entity::{TileData, TileSettings},
+        tileset_entity::TileSetPalette,
+    },
+    systems::tileset_editing::indexed_colors::{get_indexed_colors, get_nearest_indices},
+};
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+///Turns the tiles changed by the tools into a [HistoryEntry](HistoryEntry) once the mouse buttons are released
+pub fn commit_stroke(
+    mouse_input: Res<Input<MouseButton>>,
+    tool: Res<SelectedTool>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    mut tileset_query: Query<(&mut TileSetHistory, &TileSetLayers), With<CurrentlySelected>>,
+    tile_query: Query<(&TileSettings, &TileData)>,
+) {
+    //The stroke is still going
+    if current_stroke.tiles_before.is_empty()
+        || mouse_input.pressed(MouseButton::Left)
+        || mouse_input.pressed(MouseButton::Right)
+    {
+        return;
+    }
+    let (mut history, tileset_layers) = match tileset_query.single_mut() {
+        Ok(tileset) => tileset,
+        Err(_) => {
+            current_stroke.tiles_before.clear();
+            return;
+        }
+    };
+    //The tools only draw on the active layer
+    let layer = tileset_layers.active_layer;
+    let changes: Vec<TileChange> = current_stroke
+        .tiles_before
+        .drain()
+        .filter_map(|(tile_entity, before)| {
+            let (tile_settings, tile_data) = tile_query.get(tile_entity).ok()?;
+            TileChange::new(tile_entity, layer, tile_settings, &before, &tile_data.data)
+        })
+        .collect();
+    //Clicking without changing anything isn't worth remembering
+    if changes.is_empty() {
+        return;
+    }
+    history.push(HistoryEntry {
+        name: tool.name().to_string(),
+        changes,
+    });
+}
+///Sends [HistoryEvent](HistoryEvent)s for Ctrl+Z and Ctrl+Shift+Z
+pub fn history_keyboard_shortcuts(
+    keyboard_input: Res<Input<KeyCode>>,
+    egui_context: Res<EguiContext>,
+    mut history_events: EventWriter<HistoryEvent>,
+) {
+    //Undoing a typo in the gui shouldn't undo strokes
+    if egui_context.ctx().wants_keyboard_input() {
+        return;
+    }
+    let control =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let shift = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+    if control && keyboard_input.just_pressed(KeyCode::Z) {
+        history_events.send(if shift {
+            HistoryEvent::Redo
+        } else {
+            HistoryEvent::Undo
+        });
+    }
+}
+///Undoes and redoes strokes on the currently selected [TileSetBundle](crate::data::tileset_entity::TileSetBundle)
+pub fn apply_history_events(
+    mut history_events: EventReader<HistoryEvent>,
+    current_stroke: Res<CurrentStroke>,
+    mut tileset_query: Query<
+        (&mut TileSetHistory, &TileSetLayers, Option<&TileSetPalette>),
+        With<CurrentlySelected>,
+    >,
+    mut tile_query: Query<(&TileSettings, &mut TileData, &mut TileLayers)>,
+) {
+    for history_event in history_events.iter() {
+        //Undoing in the middle of a stroke would mix the stroke with the undone one
+        if !current_stroke.tiles_before.is_empty() {
+            continue;
+        }
+        if let Ok((mut history, tileset_layers, tileset_palette)) = tileset_query.single_mut() {
+            let entry = match history_event {
+                HistoryEvent::Undo => history.undo_stack.pop(),
+                HistoryEvent::Redo => history.redo_stack.pop(),
+            };
+            if let Some(entry) = entry {
+                for change in entry.changes.iter() {
+                    if let Ok((tile_settings, mut tile_data, mut tile_layers)) =
+                        tile_query.get_mut(change.tile_entity)
+                    {
+                        //The active layer's pixels are in the tile's data, the others are in it's layers
+                        let layer_data = if change.layer == tileset_layers.active_layer {
+                            &mut tile_data.data
+                        } else {
+                            match tile_layers.layers.get_mut(change.layer) {
+                                Some(layer_pixels) => &mut layer_pixels.data,
+                                None => continue,
+                            }
+                        };
+                        //Blank layers get their pixels when a stroke is put back on them
+                        if layer_data.is_empty() {
+                            *layer_data =
+                                vec![0; tile_settings.tile_width * tile_settings.tile_height * 4];
+                        }
+                        match history_event {
+                            HistoryEvent::Undo => change.undo(layer_data),
+                            HistoryEvent::Redo => change.redo(layer_data),
+                        }
+                        //The active layer's indices are found again when it's data changes, the other layers of indexed tilesets keep theirs here
+                        if let (Some(tileset_palette), Some(layer_pixels)) = (
+                            tileset_palette.filter(|_| change.layer != tileset_layers.active_layer),
+                            tile_layers.layers.get_mut(change.layer),
+                        ) {
+                            layer_pixels.indices =
+                                get_nearest_indices(&tileset_palette.palette, &layer_pixels.data);
+                            layer_pixels.data =
+                                get_indexed_colors(&tileset_palette.palette, &layer_pixels.indices);
+                        }
+                    }
+                }
+                match history_event {
+                    HistoryEvent::Undo => history.redo_stack.push(entry),
+                    HistoryEvent::Redo => history.undo_stack.push(entry),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::data::{
+    events::{ColorModeEvent, RemapPaletteIndicesEvent},
+    layers::TileLayers,
+    palette::Palette,
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TileIndices, TileSettings},
+    tileset_entity::{TileSetPalette, TileSetSettings},
+};
+use bevy::prelude::*;
+///Makes the currently selected [TileSetBundle](crate::data::tileset_entity::TileSetBundle) indexed or RGBA
+pub fn change_selected_tileset_color_mode(
+    mut commands: Commands,
+    mut color_mode_events: EventReader<ColorModeEvent>,
+    palette: Res<Palette>,
+    tileset_query: Query<(Entity, &Children), (With<TileSetSettings>, With<CurrentlySelected>)>,
+) {
+    for event in color_mode_events.iter() {
+        if let Ok((tileset_entity, children)) = tileset_query.single() {
+            match event {
+                //The tiles get their indices once the palette is there
+                ColorModeEvent::ToIndexed => {
+                    commands.entity(tileset_entity).insert(TileSetPalette {
+                        palette: palette.clone(),
+                    });
+                }
+                //The tiles already have their colors in their data
+                ColorModeEvent::ToRgba => {
+                    commands.entity(tileset_entity).remove::<TileSetPalette>();
+                    for child in children.iter() {
+                        commands.entity(*child).remove::<TileIndices>();
+                    }
+                }
+            }
+        }
+    }
+}
+///Keeps the [Palette](Palette) resource and the currently selected tileset's palette the same
+///
+///Selecting an indexed tileset shows it's palette, editing the palette afterwards changes the tileset's palette
+pub fn sync_selected_tileset_palette(
+    mut palette: ResMut<Palette>,
+    mut tileset_query: Query<
+        (&mut TileSetPalette, ChangeTrackers<CurrentlySelected>),
+        (With<TileSetSettings>, With<CurrentlySelected>),
+    >,
+) {
+    if let Ok((mut tileset_palette, selected_tracker)) = tileset_query.single_mut() {
+        if selected_tracker.is_added() || tileset_palette.is_added() {
+            if *palette != tileset_palette.palette {
+                *palette = tileset_palette.palette.clone();
+            }
+        } else if *palette != tileset_palette.palette {
+            tileset_palette.palette = palette.clone();
+        }
+    }
+}
+///Gives the tiles of indexed tilesets that don't have indices yet the indices nearest to their colors
+pub fn index_tiles(
+    mut commands: Commands,
+    tileset_query: Query<(&TileSetPalette, &Children)>,
+    mut tile_query: Query<(&TileSettings, &mut TileData, &mut TileLayers), Without<TileIndices>>,
+) {
+    for (tileset_palette, children) in tileset_query.iter() {
+        for child in children.iter() {
+            if let Ok((tile_settings, mut tile_data, mut tile_layers)) = tile_query.get_mut(*child)
+            {
+                //Blank tiles are indexed too, they get their indices when something is drawn on them
+                if !tile_data.is_blank()
+                    && tile_data.data.len()
+                        != tile_settings.tile_width * tile_settings.tile_height * 4
+                {
+                    continue;
+                }
+                let mut tile_indices = TileIndices::default();
+                quantize_tile(&tileset_palette.palette, &mut tile_data, &mut tile_indices);
+                commands.entity(*child).insert(tile_indices);
+                //The other layers are indexed too, the active one's entry is empty
+                for layer_pixels in tile_layers.layers.iter_mut() {
+                    layer_pixels.indices =
+                        get_nearest_indices(&tileset_palette.palette, &layer_pixels.data);
+                    layer_pixels.data =
+                        get_indexed_colors(&tileset_palette.palette, &layer_pixels.indices);
+                }
+            }
+        }
+    }
+}
+///Changes the colors the tools drew on indexed tiles to the nearest palette colors
+pub fn quantize_changed_tiles(
+    tileset_query: Query<&TileSetPalette>,
+    mut tile_query: Query<
+        (
+            &Parent,
+            &mut TileData,
+            &mut TileIndices,
+            ChangeTrackers<TileIndices>,
+        ),
+        Changed<TileData>,
+    >,
+) {
+    for (parent, mut tile_data, mut tile_indices, indices_tracker) in tile_query.iter_mut() {
+        //Tiles that were just indexed or loaded with their indices already have palette colors
+        if indices_tracker.is_added() {
+            continue;
+        }
+        if let Ok(tileset_palette) = tileset_query.get(parent.0) {
+            quantize_tile(&tileset_palette.palette, &mut tile_data, &mut tile_indices);
+        }
+    }
+}
+///Moves the indices of the currently selected indexed tileset's tiles along with the swatches that were removed or moved, so the tiles keep their colors
+pub fn remap_tile_indices(
+    mut remap_events: EventReader<RemapPaletteIndicesEvent>,
+    tileset_query: Query<&Children, (With<TileSetPalette>, With<CurrentlySelected>)>,
+    mut tile_query: Query<(&mut TileIndices, &mut TileLayers)>,
+) {
+    for event in remap_events.iter() {
+        if let Ok(children) = tileset_query.single() {
+            for child in children.iter() {
+                if let Ok((mut tile_indices, mut tile_layers)) = tile_query.get_mut(*child) {
+                    if indices_are_remapped(&tile_indices.indices, &event.new_indices) {
+                        remap_indices(&mut tile_indices.indices, &event.new_indices);
+                    }
+                    //Only touching the layers when their indices move, so the other tiles' textures aren't updated
+                    if tile_layers.layers.iter().any(|layer_pixels| {
+                        indices_are_remapped(&layer_pixels.indices, &event.new_indices)
+                    }) {
+                        for layer_pixels in tile_layers.layers.iter_mut() {
+                            remap_indices(&mut layer_pixels.indices, &event.new_indices);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+///Recolors every tile of the tilesets whose palette was edited
+pub fn recolor_tiles_with_changed_palette(
+    tileset_query: Query<(&TileSetPalette, &Children), Changed<TileSetPalette>>,
+    mut tile_query: Query<(&mut TileData, &TileIndices, &mut TileLayers)>,
+) {
+    for (tileset_palette, children) in tileset_query.iter() {
+        for child in children.iter() {
+            if let Ok((mut tile_data, tile_indices, mut tile_layers)) = tile_query.get_mut(*child) {
+                //Only changing the tiles that look different, so only their textures are updated
+                let layers_data: Vec<Vec<u8>> = tile_layers
+                    .layers
+                    .iter()
+                    .map(|layer_pixels| {
+                        get_indexed_colors(&tileset_palette.palette, &layer_pixels.indices)
+                    })
+                    .collect();
+                if tile_layers
+                    .layers
+                    .iter()
+                    .zip(layers_data.iter())
+                    .any(|(layer_pixels, data)| layer_pixels.data != *data)
+                {
+                    for (layer_pixels, data) in tile_layers.layers.iter_mut().zip(layers_data) {
+                        layer_pixels.data = data;
+                    }
+                }
+                let data = get_indexed_colors(&tileset_palette.palette, &tile_indices.indices);
+                if tile_data.data != data {
+                    tile_data.data = data;
+                }
+            }
+        }
+    }
+}
+///Sets a tile's indices to the palette colors nearest to it's pixels, and it's pixels to those colors
+///
+///The tile's data is only changed if a pixel wasn't already a palette color, pixels that still have their index's color keep it
+fn quantize_tile(palette: &Palette, tile_data: &mut Mut<TileData>, tile_indices: &mut TileIndices) {
+    //A palette can have the same color more than once, the nearest index isn't always the pixel's index
+    let indices: Vec<u8> = tile_data
+        .data
+        .chunks_exact(4)
+        .enumerate()
+        .map(|(pixel_index, pixel)| {
+            let pixel = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            match tile_indices.indices.get(pixel_index) {
+                Some(index) if palette.indexed_color(*index) == pixel => *index,
+                _ => palette.nearest_index(pixel),
+            }
+        })
+        .collect();
+    let data = get_indexed_colors(palette, &indices);
+    if tile_data.data != data {
+        tile_data.data = data;
+    }
+    tile_indices.indices = indices;
+}
+///Checks whether some of the indices get a new index
+fn indices_are_remapped(indices: &[u8], new_indices: &[u8]) -> bool {
+    indices.iter().any(|index| {
+        new_indices
+            .get(*index as usize)
+            .map_or(false, |new| new != index)
+    })
+}
+///Changes every index to it's new index, indices without one stay the same
+pub fn remap_indices(indices: &mut [u8], new_indices: &[u8]) {
+    for index in indices.iter_mut() {
+        if let Some(new_index) = new_indices.get(*index as usize) {
+            *index = *new_index;
+        }
+    }
+}
+///Returns the RGBA8 pixels of palette indices
+pub fn get_indexed_colors(palette: &Palette, indices: &[u8]) -> Vec<u8> {
+    indices
+        .iter()
+        .flat_map(|index| palette.indexed_color(*index).to_vec())
+        .collect()
+}
+///Returns the palette indices nearest to RGBA8 pixels
+pub fn get_nearest_indices(palette: &Palette, data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .map(|pixel| palette.nearest_index([pixel[0], pixel[1], pixel[2], pixel[3]]))
+        .collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::palette::{Swatch, TRANSPARENT_INDEX};
+
+    fn swatch(color: [u8; 4]) -> Swatch {
+        Swatch {
+            color,
+            name: String::new(),
+        }
+    }
+
+    fn get_palette() -> Palette {
+        Palette {
+            name: "Test".to_string(),
+            swatches: vec![
+                swatch([255, 0, 0, 255]),
+                swatch([0, 255, 0, 255]),
+                swatch([0, 0, 255, 255]),
+            ],
+        }
+    }
+
+    #[test]
+    fn moving_a_swatch_keeps_the_tile_colors() {
+        let mut palette = get_palette();
+        let mut indices = vec![0, 1, 2, TRANSPARENT_INDEX];
+        let data = get_indexed_colors(&palette, &indices);
+        let new_indices = palette.swap_swatches(1, 2);
+        remap_indices(&mut indices, &new_indices);
+        assert_eq!(indices, vec![0, 2, 1, TRANSPARENT_INDEX]);
+        assert_eq!(get_indexed_colors(&palette, &indices), data);
+    }
+
+    #[test]
+    fn removing_a_swatch_keeps_the_other_colors() {
+        let mut palette = get_palette();
+        palette.swatches.push(swatch([250, 0, 0, 255]));
+        let mut indices = vec![0, 1, 2, 3];
+        let new_indices = palette.remove_swatch(0);
+        remap_indices(&mut indices, &new_indices);
+        //The removed red goes to the red that's left
+        assert_eq!(indices, vec![2, 0, 1, 2]);
+        assert_eq!(
+            get_indexed_colors(&palette, &indices),
+            [
+                [250, 0, 0, 255],
+                [0, 255, 0, 255],
+                [0, 0, 255, 255],
+                [250, 0, 0, 255]
+            ]
+            .concat()
+        );
+    }
+}
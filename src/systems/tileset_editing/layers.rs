@@ -0,0 +1,113 @@
+use crate::data::{
+    events::LayerEvent,
+    history::{CurrentStroke, TileSetHistory},
+    layers::{LayerPixels, LayerSettings, TileLayers, TileSetLayers},
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TileIndices, TileSettings},
+};
+use bevy::prelude::*;
+///Adds, removes and selects layers of the currently selected [TileSetBundle](crate::data::tileset_entity::TileSetBundle)
+pub fn apply_layer_events(
+    mut layer_events: EventReader<LayerEvent>,
+    current_stroke: Res<CurrentStroke>,
+    mut tileset_query: Query<
+        (&mut TileSetLayers, &mut TileSetHistory, &Children),
+        With<CurrentlySelected>,
+    >,
+    mut tile_query: Query<(
+        &TileSettings,
+        &mut TileData,
+        &mut TileLayers,
+        Option<&mut TileIndices>,
+    )>,
+) {
+    for layer_event in layer_events.iter() {
+        //The stroke's tiles were recorded from the active layer
+        if !current_stroke.tiles_before.is_empty() {
+            continue;
+        }
+        let (mut tileset_layers, mut history, children) = match tileset_query.single_mut() {
+            Ok(tileset) => tileset,
+            Err(_) => continue,
+        };
+        match *layer_event {
+            //New layers go on top and become the active layer
+            LayerEvent::Add => {
+                let name = format!("Layer {}", tileset_layers.layers.len() + 1);
+                tileset_layers.layers.push(LayerSettings::new(name));
+                for child in children.iter() {
+                    //The new layer is blank in every tile
+                    if let Ok((_, _, mut tile_layers, _)) = tile_query.get_mut(*child) {
+                        tile_layers.layers.push(LayerPixels::default());
+                    }
+                }
+                let new_layer = tileset_layers.layers.len() - 1;
+                select_layer(&mut tileset_layers, new_layer, children, &mut tile_query);
+            }
+            LayerEvent::Remove(layer) => {
+                //A tileset always has a layer to draw on
+                if tileset_layers.layers.len() <= 1 || layer >= tileset_layers.layers.len() {
+                    continue;
+                }
+                if layer == tileset_layers.active_layer {
+                    let next_active_layer = if layer == 0 { 1 } else { layer - 1 };
+                    select_layer(
+                        &mut tileset_layers,
+                        next_active_layer,
+                        children,
+                        &mut tile_query,
+                    );
+                }
+                tileset_layers.layers.remove(layer);
+                if tileset_layers.active_layer > layer {
+                    tileset_layers.active_layer -= 1;
+                }
+                for child in children.iter() {
+                    if let Ok((_, _, mut tile_layers, _)) = tile_query.get_mut(*child) {
+                        if layer < tile_layers.layers.len() {
+                            tile_layers.layers.remove(layer);
+                        }
+                    }
+                }
+                //The strokes remember the layers by their index, the ones above the removed layer move down with it
+                history.remove_layer(layer);
+            }
+            LayerEvent::Select(layer) => {
+                if layer < tileset_layers.layers.len() && layer != tileset_layers.active_layer {
+                    select_layer(&mut tileset_layers, layer, children, &mut tile_query);
+                }
+            }
+        }
+    }
+}
+///Makes another layer the active one, moving the pixels of the old active layer from the tiles' [TileData](TileData) to their [TileLayers](TileLayers) and the new one's the other way
+fn select_layer(
+    tileset_layers: &mut TileSetLayers,
+    layer: usize,
+    children: &Children,
+    tile_query: &mut Query<(
+        &TileSettings,
+        &mut TileData,
+        &mut TileLayers,
+        Option<&mut TileIndices>,
+    )>,
+) {
+    let active_layer = tileset_layers.active_layer;
+    for child in children.iter() {
+        if let Ok((_, mut tile_data, mut tile_layers, tile_indices)) = tile_query.get_mut(*child) {
+            let tile_layers = &mut *tile_layers;
+            if active_layer >= tile_layers.layers.len() || layer >= tile_layers.layers.len() {
+                continue;
+            }
+            tile_layers.layers[active_layer].data = std::mem::take(&mut tile_data.data);
+            tile_data.data = std::mem::take(&mut tile_layers.layers[layer].data);
+            //Indexed tiles keep the indices of every layer too
+            if let Some(mut tile_indices) = tile_indices {
+                tile_layers.layers[active_layer].indices =
+                    std::mem::take(&mut tile_indices.indices);
+                tile_indices.indices = std::mem::take(&mut tile_layers.layers[layer].indices);
+            }
+        }
+    }
+    tileset_layers.active_layer = layer;
+}
@@ -1,2 +1,5 @@
+pub mod history;
+pub mod indexed_colors;
+pub mod layers;
 pub mod tools;
 pub mod updating_data;
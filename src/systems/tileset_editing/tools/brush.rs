@@ -1,261 +1,433 @@
 use bevy::prelude::*;
 
+use super::{
+    can_draw_on_active_layer, get_pressed_drawing_button,
+    picker::is_alt_pressed,
+    rasterizing::{get_line_pixels, is_l_shaped_corner},
+};
+
 use crate::data::{
     assets::Pattern,
-    resources::{MousePixelPosition, SelectedTool},
+    history::CurrentStroke,
+    layers::TileSetLayers,
+    resources::{BrushMode, BrushSettings, DrawingColors, MousePixelPosition, SelectedTool},
+    selection::SelectionMask,
     shared_components::CurrentlySelected,
-    tile_entity::{TileData, TilePosition, TileSettings},
+    tile_entity::{TileData, TilePosition, TileSettings, TileTexture},
+    tileset_entity::{TileSetSettings, TileSetTiles},
 };
+///What the brush remembers between frames while a stroke is going
+#[derive(Debug, Default)]
+pub struct BrushStrokeState {
+    ///The mouse pixel from the last frame, stamps are placed on the line from it to the current one
+    last_mouse_pixel: Option<IVec2>,
+    ///With pixel perfect on, a stamp waits until the next one shows it isn't an L shaped corner
+    pending_pixel: Option<IVec2>,
+    ///The last pixel that was actually stamped
+    last_stamped_pixel: Option<IVec2>,
+    ///The mouse button that started the stroke, it decides the stroke's color
+    button: Option<MouseButton>,
+}
+impl BrushStrokeState {
+    ///Moves a pixel perfect stroke to the next pixel, returns the pending pixel if it has to be stamped now
+    ///
+    ///The pending pixel is skipped when it's an L shaped corner between the last stamped pixel and the next one
+    pub(crate) fn advance_pixel_perfect(&mut self, pixel: IVec2) -> Option<IVec2> {
+        let stamped_pixel = match (self.last_stamped_pixel, self.pending_pixel) {
+            (Some(last_stamped_pixel), Some(pending_pixel))
+                if is_l_shaped_corner(last_stamped_pixel, pending_pixel, pixel) =>
+            {
+                None
+            }
+            (_, pending_pixel) => pending_pixel,
+        };
+        if stamped_pixel.is_some() {
+            self.last_stamped_pixel = stamped_pixel;
+        }
+        self.pending_pixel = Some(pixel);
+        stamped_pixel
+    }
+}
 pub fn brush_tool(
+    mut stroke_state: Local<BrushStrokeState>,
     mouse_pixel_position: Res<MousePixelPosition>,
     mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
     tool: Res<SelectedTool>,
+    brush_settings: Res<BrushSettings>,
+    drawing_colors: Res<DrawingColors>,
     patterns: Res<Assets<Pattern>>,
-    mut query: Query<
-        (Entity, &TileSettings, &TilePosition, &mut TileData),
-        With<CurrentlySelected>,
-    >,
+    mut current_stroke: ResMut<CurrentStroke>,
+    layers_query: Query<&TileSetLayers, With<CurrentlySelected>>,
+    mask_query: Query<&SelectionMask, With<CurrentlySelected>>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    mut query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
 ) {
-    //If the user is pressing the left mouse button
-    if mouse_input.pressed(MouseButton::Left) {
-        //And his current tool is the Brush
-        match &*tool {
-            SelectedTool::Pencil { pattern_handle } => {
-                //If the user is hovering on the tileset and
-                if let Some(mouse_pixel) = mouse_pixel_position.pixel_position {
-                    let pattern = patterns.get(pattern_handle).unwrap();
-                    //Get the pattern's corner pixels' positions
-                    let (top_left_pixel, top_right_pixel, bottom_left_pixel, bottom_right_pixel) = (
-                        (mouse_pixel.as_i32()
-                            + IVec2::new(-(pattern.size.x as i32 / 2), pattern.size.y as i32 / 2))
-                        .as_u32(),
-                        (mouse_pixel.as_i32()
-                            + IVec2::new(pattern.size.x as i32 / 2, pattern.size.y as i32 / 2))
-                        .as_u32(),
-                        (mouse_pixel.as_i32()
-                            + IVec2::new(
-                                -(pattern.size.x as i32 / 2),
-                                -(pattern.size.y as i32 / 2),
-                            ))
-                        .as_u32(),
-                        (mouse_pixel.as_i32()
-                            + IVec2::new(pattern.size.x as i32 / 2, -(pattern.size.y as i32 / 2)))
-                        .as_u32(),
+    //If the current tool is one of the brushes and the pattern finished loading
+    let pattern = match tool
+        .pattern_handle()
+        .and_then(|pattern_handle| patterns.get(pattern_handle))
+    {
+        Some(pattern) => pattern,
+        None => {
+            *stroke_state = BrushStrokeState::default();
+            return;
+        }
+    };
+    //The stamps find the tiles they touch by their position in the selected tileset
+    let tileset = match tileset_query.single() {
+        Ok(tileset) => tileset,
+        Err(_) => {
+            *stroke_state = BrushStrokeState::default();
+            return;
+        }
+    };
+    //Alt turns the pencil into the picker for as long as it's held
+    let is_picking =
+        matches!(*tool, SelectedTool::Pencil { .. }) && is_alt_pressed(&keyboard_input);
+    let pressed_button = if is_picking || !can_draw_on_active_layer(&layers_query) {
+        None
+    } else {
+        get_pressed_drawing_button(&mouse_input)
+    };
+    //The stroke keeps the color of the button it started with
+    let button = stroke_state
+        .button
+        .or(pressed_button)
+        .unwrap_or(MouseButton::Left);
+    let operation = match &*tool {
+        SelectedTool::Eraser { .. } => BrushOperation::Erase {
+            alpha_decrease: brush_settings.eraser_strength,
+        },
+        _ => match brush_settings.mode {
+            BrushMode::Mask => BrushOperation::Paint {
+                color: drawing_colors.get(button),
+            },
+            BrushMode::Stamp => BrushOperation::Stamp,
+        },
+    };
+    //Only the pixels selected with the lasso or the magic wand get painted
+    let selection_mask = mask_query.single().ok();
+    //If the user is pressing a drawing mouse button
+    if pressed_button.is_some() {
+        stroke_state.button = Some(button);
+        //If the user is hovering on the tileset
+        if let Some(mouse_pixel) = mouse_pixel_position.pixel_position {
+            let mouse_pixel = mouse_pixel.as_i32();
+            //Fast mouse movements skip pixels, so we stamp along the line from the last frame's pixel
+            let mut stroke_pixels = match stroke_state.last_mouse_pixel {
+                Some(last_mouse_pixel) => get_line_pixels(last_mouse_pixel, mouse_pixel),
+                None => vec![mouse_pixel],
+            };
+            //The last frame's pixel was already handled
+            if stroke_state.last_mouse_pixel.is_some() {
+                stroke_pixels.remove(0);
+            }
+            for pixel in stroke_pixels {
+                if !brush_settings.pixel_perfect {
+                    stamp_pattern(
+                        pattern,
+                        operation,
+                        pixel,
+                        selection_mask,
+                        tileset,
+                        &mut current_stroke,
+                        &mut query,
                     );
-
-                    println!(
-                        "{:?}------{:?}\n---{:?}---\n{:?}------{:?}\n",
-                        top_left_pixel,
-                        top_right_pixel,
-                        mouse_pixel,
-                        bottom_left_pixel,
-                        bottom_right_pixel
+                    continue;
+                }
+                if let Some(stamped_pixel) = stroke_state.advance_pixel_perfect(pixel) {
+                    stamp_pattern(
+                        pattern,
+                        operation,
+                        stamped_pixel,
+                        selection_mask,
+                        tileset,
+                        &mut current_stroke,
+                        &mut query,
                     );
-                    for (_entity, tile_settings, tile_position, mut tile_data) in query.iter_mut() {
-                        let tile_min_pixel = UVec2::new(
-                            tile_position.position.x * tile_settings.tile_width as u32,
-                            tile_position.position.y * tile_settings.tile_height as u32,
-                        );
-                        let tile_max_pixel = UVec2::new(
-                            (tile_position.position.x + 1) * tile_settings.tile_width as u32 - 1,
-                            (tile_position.position.y + 1) * tile_settings.tile_height as u32 - 1,
-                        );
-
-                        //Collision Check
-                        if (top_left_pixel.x >= tile_min_pixel.x
-                            && top_left_pixel.y >= tile_min_pixel.y)
-                            && (top_left_pixel.x <= tile_max_pixel.x
-                                && top_left_pixel.y <= tile_max_pixel.y)
-                        {
-                            let pixel_in_tile_coords = top_left_pixel - tile_min_pixel;
-
-                            draw_pixels_in_tile(
-                                pixel_in_tile_coords,
-                                Horizontal::Left,
-                                Vertical::Top,
-                                &mut tile_data,
-                                tile_settings,
-                                pattern,
-                            );
-                        } else if (top_right_pixel.x >= tile_min_pixel.x
-                            && top_right_pixel.y >= tile_min_pixel.y)
-                            && (top_right_pixel.x <= tile_max_pixel.x
-                                && top_right_pixel.y <= tile_max_pixel.y)
-                        {
-                            let pixel_in_tile_coords = top_right_pixel - tile_min_pixel;
-
-                            draw_pixels_in_tile(
-                                pixel_in_tile_coords,
-                                Horizontal::Right,
-                                Vertical::Top,
-                                &mut tile_data,
-                                tile_settings,
-                                pattern,
-                            );
-                        } else if (bottom_left_pixel.x >= tile_min_pixel.x
-                            && bottom_left_pixel.y >= tile_min_pixel.y)
-                            && (bottom_left_pixel.x <= tile_max_pixel.x
-                                && bottom_left_pixel.y <= tile_max_pixel.y)
-                        {
-                            let pixel_in_tile_coords = bottom_left_pixel - tile_min_pixel;
-
-                            draw_pixels_in_tile(
-                                pixel_in_tile_coords,
-                                Horizontal::Left,
-                                Vertical::Bottom,
-                                &mut tile_data,
-                                tile_settings,
-                                pattern,
-                            );
-                        } else if (bottom_right_pixel.x >= tile_min_pixel.x
-                            && bottom_right_pixel.y >= tile_min_pixel.y)
-                            && (bottom_right_pixel.x <= tile_max_pixel.x
-                                && bottom_right_pixel.y <= tile_max_pixel.y)
-                        {
-                            let pixel_in_tile_coords = bottom_right_pixel - tile_min_pixel;
-                            draw_pixels_in_tile(
-                                pixel_in_tile_coords,
-                                Horizontal::Right,
-                                Vertical::Bottom,
-                                &mut tile_data,
-                                tile_settings,
-                                pattern,
-                            );
-                        }
-                    }
                 }
             }
-            _ => {}
+            stroke_state.last_mouse_pixel = Some(mouse_pixel);
+        } else {
+            //The line can't continue outside of the tileset
+            stroke_state.last_mouse_pixel = None;
+        }
+    } else {
+        //The stroke ended, the pixel that was waiting can't be a corner anymore
+        if let Some(pending_pixel) = stroke_state.pending_pixel {
+            stamp_pattern(
+                pattern,
+                operation,
+                pending_pixel,
+                selection_mask,
+                tileset,
+                &mut current_stroke,
+                &mut query,
+            );
         }
+        *stroke_state = BrushStrokeState::default();
     }
 }
-
-#[derive(Debug)]
-enum Horizontal {
-    Left,
-    Right,
+///What a brush does to the pixels under it's pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushOperation {
+    ///Paints the pattern's non transparent pixels with a color
+    Paint { color: [u8; 4] },
+    ///Copies the pattern's pixels
+    Stamp,
+    ///Lowers the alpha of the pixels under the pattern's non transparent pixels
+    Erase { alpha_decrease: u8 },
 }
-#[derive(Debug)]
-enum Vertical {
-    Top,
-    Bottom,
-}
-#[derive(Debug)]
-struct XData {
-    tile_x_min: u32,
-    tile_x_max: u32,
-    pattern_start_x: u32,
-    pattern_end_x: u32,
-}
-#[derive(Debug)]
-struct YData {
-    tile_y_min: u32,
-    tile_y_max: u32,
-    pattern_start_y: u32,
-    pattern_end_y: u32,
-}
-fn get_x_for_drawing_loop(
-    side: &Horizontal,
+///Applies a pattern centered on a tileset pixel to every tile it overlaps
+///
+///Only the tiles under the pattern are looked at, so a stamp costs the same on small and big tilesets
+pub fn stamp_pattern(
     pattern: &Pattern,
-    pixel_in_tile_coords: UVec2,
-    tile_settings: &TileSettings,
-) -> XData {
-    //println!("get_x::pixel_in_tile_coords:{:?}", pixel_in_tile_coords);
-    let tile_min_pixel = UVec2::ZERO;
-    match side {
-        Horizontal::Left => XData {
-            //we are the left corner, from left to right, we start at our pos
-            tile_x_min: pixel_in_tile_coords.x,
-            //we need to end either after the pattern ends, or at the end of the tile
-            tile_x_max: u32::min(
-                pixel_in_tile_coords.x + pattern.size.x,
-                tile_settings.tile_width as u32,
-            ),
-            //we are the left corner, our pattern's start is on zero
-            pattern_start_x: 0,
-            //we either end when our pattern ends, or at the tile's end
-            pattern_end_x: u32::min(
-                pattern.size.x,
-                tile_settings.tile_width as u32 - pixel_in_tile_coords.x,
-            ),
-        },
-        Horizontal::Right => XData {
-            //we are the right corner, from left to right, we either start at where our pattern can fit, or at the start of the tile
-            tile_x_min: i32::max(
-                tile_min_pixel.x as i32,
-                pixel_in_tile_coords.x as i32 - pattern.size.x as i32,
-            ) as u32,
-            //we are the right corner, the pattern ends on us
-            tile_x_max: pixel_in_tile_coords.x + 1,
-            //we are the right corner, our pattern's start is dependant if the pattern can fit, else, what's left
-            pattern_start_x: i32::max(0, pattern.size.x as i32 - 1 - pixel_in_tile_coords.x as i32)
-                as u32,
-            //we either end when our pattern ends, or at the tile's end
-            pattern_end_x: pattern.size.x,
-        },
+    operation: BrushOperation,
+    center_pixel: IVec2,
+    selection_mask: Option<&SelectionMask>,
+    (tileset_settings, tileset_tiles): (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    //This is the pattern's bottom left pixel in the tileset
+    let pattern_min_pixel =
+        center_pixel - IVec2::new(pattern.size.x as i32 / 2, pattern.size.y as i32 / 2);
+    let pattern_max_pixel =
+        pattern_min_pixel + IVec2::new(pattern.size.x as i32, pattern.size.y as i32);
+    //Every tile the pattern overlaps gets the part of the pattern that's inside it
+    for tile_entity in
+        tileset_tiles.get_overlapped(tileset_settings, pattern_min_pixel, pattern_max_pixel)
+    {
+        let (entity, tile_settings, tile_position, mut tile_data, mut tile_texture) =
+            match query.get_mut(tile_entity) {
+                Ok(tile) => tile,
+                Err(_) => continue,
+            };
+        if let Some(overlap) =
+            get_pattern_overlap_with_tile(pattern, pattern_min_pixel, tile_settings, tile_position)
+        {
+            current_stroke.record(entity, &tile_data);
+            draw_pattern_in_tile(
+                pattern,
+                operation,
+                pattern_min_pixel,
+                &overlap,
+                selection_mask,
+                &mut tile_data,
+                tile_settings,
+                tile_position,
+            );
+            //Only the part of the tile under the pattern gets uploaded
+            let tile_min_pixel = tile_position.min_pixel(tile_settings).as_i32();
+            tile_texture.mark_dirty_rect(
+                tile_settings,
+                (overlap.min - tile_min_pixel).as_u32(),
+                (overlap.max - tile_min_pixel).as_u32(),
+            );
+        }
     }
 }
-///FIXME:idk how and why this works, this will probably be technical debt later
-fn get_y_for_drawing_loop(
-    side: &Vertical,
+///The tileset pixels a pattern and a tile have in common, the max is exclusive
+#[derive(Debug, Clone, Copy)]
+pub struct PixelOverlap {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+///Returns the pixels of a tile that a pattern placed at some tileset pixel covers, None if it doesn't touch the tile
+pub fn get_pattern_overlap_with_tile(
     pattern: &Pattern,
-    pixel_in_tile_coords: UVec2,
+    pattern_min_pixel: IVec2,
     tile_settings: &TileSettings,
-) -> YData {
-    let tile_min_pixel = UVec2::ZERO;
-    match side {
-        Vertical::Top => YData {
-            tile_y_min: i32::max(
-                tile_min_pixel.y as i32,
-                pixel_in_tile_coords.y as i32 - (pattern.size.y as i32 - 1),
-            ) as u32,
-            tile_y_max: u32::min(pixel_in_tile_coords.y + 1, tile_settings.tile_height as u32),
-            pattern_start_y: i32::max(0, pattern.size.y as i32 - 1 - pixel_in_tile_coords.y as i32)
-                as u32,
-            pattern_end_y: pattern.size.y,
-        },
-
-        Vertical::Bottom => YData {
-            //FIXME: THERE'S A BUG HERE
-            tile_y_min: pixel_in_tile_coords.y,
-            tile_y_max: u32::min(
-                pixel_in_tile_coords.y + pattern.size.y,
-                tile_settings.tile_height as u32,
-            ),
-            pattern_start_y: 0,
-            pattern_end_y: u32::min(
-                pattern.size.y,
-                tile_settings.tile_height as u32 - pixel_in_tile_coords.y,
-            ),
-        },
+    tile_position: &TilePosition,
+) -> Option<PixelOverlap> {
+    let tile_min_pixel = tile_position.min_pixel(tile_settings).as_i32();
+    let tile_max_pixel = tile_min_pixel
+        + IVec2::new(
+            tile_settings.tile_width as i32,
+            tile_settings.tile_height as i32,
+        );
+    let pattern_max_pixel =
+        pattern_min_pixel + IVec2::new(pattern.size.x as i32, pattern.size.y as i32);
+    let overlap = PixelOverlap {
+        min: IVec2::new(
+            i32::max(pattern_min_pixel.x, tile_min_pixel.x),
+            i32::max(pattern_min_pixel.y, tile_min_pixel.y),
+        ),
+        max: IVec2::new(
+            i32::min(pattern_max_pixel.x, tile_max_pixel.x),
+            i32::min(pattern_max_pixel.y, tile_max_pixel.y),
+        ),
+    };
+    if overlap.min.x < overlap.max.x && overlap.min.y < overlap.max.y {
+        Some(overlap)
+    } else {
+        None
     }
 }
-fn draw_pixels_in_tile(
-    pixel_in_tile_coords: UVec2,
-    horizontal: Horizontal,
-    vertical: Vertical,
+///Applies the part of the pattern that's inside the overlap to the tile
+fn draw_pattern_in_tile(
+    pattern: &Pattern,
+    operation: BrushOperation,
+    pattern_min_pixel: IVec2,
+    overlap: &PixelOverlap,
+    selection_mask: Option<&SelectionMask>,
     tile_data: &mut TileData,
     tile_settings: &TileSettings,
-    pattern: &Pattern,
+    tile_position: &TilePosition,
 ) {
-    let x_data = get_x_for_drawing_loop(&horizontal, pattern, pixel_in_tile_coords, tile_settings);
-    let y_data = get_y_for_drawing_loop(&vertical, pattern, pixel_in_tile_coords, tile_settings);
-    println!(
-        "Vertical:{:?},Horizontal:{:?}\npixel_in_tile_coords:{:?}\nx_data:{:?}\ny_data:{:?}",
-        vertical, horizontal, pixel_in_tile_coords, x_data, y_data
-    );
-    for (i_y, y_tile) in (y_data.tile_y_min..y_data.tile_y_max).enumerate() {
-        for (i_x, x_tile) in (x_data.tile_x_min..x_data.tile_x_max).enumerate() {
-            for p in 0..4 {
-                tile_data.data[(tile_settings.tile_height - 1 - y_tile as usize)
-                    * tile_settings.tile_width
-                    * 4
-                    + (x_tile as usize) * 4
-                    + p] = pattern.pattern_pixels[(y_data.pattern_start_y as usize + i_y)
-                    * pattern.size.x as usize
-                    + (x_data.pattern_start_x as usize + i_x)][p];
+    let tile_min_pixel = tile_position.min_pixel(tile_settings).as_i32();
+    for y in overlap.min.y..overlap.max.y {
+        for x in overlap.min.x..overlap.max.x {
+            if !selection_mask.map_or(true, |selection_mask| {
+                selection_mask.can_paint(IVec2::new(x, y))
+            }) {
+                continue;
+            }
+            let pixel_in_pattern = IVec2::new(x, y) - pattern_min_pixel;
+            let pixel_in_tile = IVec2::new(x, y) - tile_min_pixel;
+            let pattern_pixel = pattern.pattern_pixels[pixel_in_pattern.y as usize
+                * pattern.size.x as usize
+                + pixel_in_pattern.x as usize];
+            match operation {
+                //The pattern's transparent pixels aren't part of it's shape
+                BrushOperation::Paint { color } if pattern_pixel[3] > 0 => {
+                    tile_data.set_pixel(tile_settings, pixel_in_tile.as_u32(), color)
+                }
+                BrushOperation::Paint { .. } => {}
+                BrushOperation::Stamp => {
+                    tile_data.set_pixel(tile_settings, pixel_in_tile.as_u32(), pattern_pixel)
+                }
+                BrushOperation::Erase { alpha_decrease } if pattern_pixel[3] > 0 => {
+                    let mut pixel = tile_data.get_pixel(tile_settings, pixel_in_tile.as_u32());
+                    pixel[3] = pixel[3].saturating_sub(alpha_decrease);
+                    //Fully transparent pixels are stored the same way as untouched ones
+                    if pixel[3] == 0 {
+                        pixel = [0; 4];
+                    }
+                    tile_data.set_pixel(tile_settings, pixel_in_tile.as_u32(), pixel);
+                }
+                BrushOperation::Erase { .. } => {}
             }
         }
     }
-    println!("");
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(pixels: &[(i32, i32)]) -> Vec<IVec2> {
+        pixels.iter().map(|(x, y)| IVec2::new(*x, *y)).collect()
+    }
+    ///Four 4x4 tiles, two by two
+    const TILESET_SETTINGS: TileSetSettings = TileSetSettings {
+        tile_width: 4,
+        tile_height: 4,
+        tileset_width: 2,
+        tileset_height: 2,
+    };
+    const TILE_SETTINGS: TileSettings = TileSettings {
+        tile_width: 4,
+        tile_height: 4,
+    };
+    ///Returns the tiles a 3x3 stamp centered on a pixel touches, with the tileset pixels it covers in each of them
+    fn stamp_overlaps(center_pixel: (i32, i32)) -> Vec<((u32, u32), (i32, i32), (i32, i32))> {
+        let pattern = Pattern {
+            size: UVec2::new(3, 3),
+            pattern_pixels: vec![[255; 4]; 9],
+        };
+        let tileset_tiles = TileSetTiles {
+            tiles: (0..4).map(Entity::new).collect(),
+        };
+        let pattern_min_pixel = IVec2::new(center_pixel.0, center_pixel.1) - IVec2::ONE;
+        tileset_tiles
+            .get_overlapped(
+                &TILESET_SETTINGS,
+                pattern_min_pixel,
+                pattern_min_pixel + IVec2::new(3, 3),
+            )
+            .into_iter()
+            .map(|tile_entity| {
+                let tile_position = TilePosition {
+                    position: UVec2::new(tile_entity.id() % 2, tile_entity.id() / 2),
+                };
+                let overlap = get_pattern_overlap_with_tile(
+                    &pattern,
+                    pattern_min_pixel,
+                    &TILE_SETTINGS,
+                    &tile_position,
+                )
+                .expect("Every overlapped tile has some of the stamp");
+                (
+                    (tile_position.position.x, tile_position.position.y),
+                    (overlap.min.x, overlap.min.y),
+                    (overlap.max.x, overlap.max.y),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stamps_on_a_vertical_edge_are_split_between_two_tiles() {
+        assert_eq!(
+            stamp_overlaps((4, 1)),
+            vec![((0, 0), (3, 0), (4, 3)), ((1, 0), (4, 0), (6, 3))]
+        );
+    }
+
+    #[test]
+    fn stamps_on_a_tile_corner_are_split_between_four_tiles() {
+        assert_eq!(
+            stamp_overlaps((4, 4)),
+            vec![
+                ((0, 0), (3, 3), (4, 4)),
+                ((1, 0), (4, 3), (6, 4)),
+                ((0, 1), (3, 4), (4, 6)),
+                ((1, 1), (4, 4), (6, 6))
+            ]
+        );
+    }
+
+    #[test]
+    fn stamps_hanging_off_the_tileset_only_keep_the_pixels_inside_it() {
+        assert_eq!(stamp_overlaps((0, 0)), vec![((0, 0), (0, 0), (2, 2))]);
+        assert_eq!(stamp_overlaps((7, 7)), vec![((1, 1), (6, 6), (8, 8))]);
+    }
+
+    #[test]
+    fn pixel_perfect_strokes_skip_the_corners() {
+        let mut stroke_state = BrushStrokeState::default();
+        let mut stamped: Vec<IVec2> = pixels(&[(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 2)])
+            .into_iter()
+            .filter_map(|pixel| stroke_state.advance_pixel_perfect(pixel))
+            .collect();
+        //The last pixel is stamped when the stroke ends
+        stamped.extend(stroke_state.pending_pixel);
+        assert_eq!(stamped, pixels(&[(0, 0), (1, 1), (2, 2), (3, 2)]));
+    }
+
+    #[test]
+    fn pixel_perfect_straight_lines_keep_every_pixel() {
+        let mut stroke_state = BrushStrokeState::default();
+        let stroke = pixels(&[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        let mut stamped: Vec<IVec2> = stroke
+            .iter()
+            .filter_map(|pixel| stroke_state.advance_pixel_perfect(*pixel))
+            .collect();
+        stamped.extend(stroke_state.pending_pixel);
+        assert_eq!(stamped, stroke);
+    }
 }
@@ -0,0 +1,167 @@
+use std::{error::Error, time::Instant};
+
+use bevy::prelude::*;
+
+use super::{
+    brush::{stamp_pattern, BrushOperation},
+    rasterizing::get_line_pixels,
+};
+use crate::data::{
+    assets::Pattern,
+    history::CurrentStroke,
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TilePosition, TileSettings, TileTexture},
+    tileset_entity::{TileSetSettings, TileSetTiles},
+};
+///The tileset sizes the benchmark paints on, in tiles, the stamps should cost the same on all of them
+const BENCHMARK_TILESET_SIZES: [usize; 3] = [8, 32, 64];
+///The size of the benchmark's tiles in pixels
+const BENCHMARK_TILE_SIZE: usize = 16;
+///The strokes the benchmark paints in a frame, as the pixels the brush is stamped on
+#[derive(Debug, Default)]
+struct BenchmarkStrokes {
+    pixels: Vec<IVec2>,
+}
+///Paints strokes across tilesets of a few sizes without opening a window, and prints how long a stamp takes on each
+///
+///The arguments are `[frames]`, every frame paints a stroke along each row of tiles and one along each diagonal
+pub fn run_brush_benchmark_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let frames = match args {
+        [] => 10,
+        [frames] => frames.parse::<u32>()?,
+        _ => return Err("usage: benchmark-brush [frames]".into()),
+    };
+    for tileset_size in BENCHMARK_TILESET_SIZES.iter().copied() {
+        let tileset_settings = TileSetSettings {
+            tile_width: BENCHMARK_TILE_SIZE,
+            tile_height: BENCHMARK_TILE_SIZE,
+            tileset_width: tileset_size,
+            tileset_height: tileset_size,
+        };
+        let mut world = World::default();
+        spawn_benchmark_tileset(&mut world, tileset_settings);
+        let strokes = get_benchmark_strokes(&tileset_settings);
+        let stamps = strokes.pixels.len() as u32 * frames;
+        world.insert_resource(strokes);
+        world.insert_resource(get_benchmark_pattern());
+        world.insert_resource(CurrentStroke::default());
+        let mut stage =
+            SystemStage::single_threaded().with_system(paint_benchmark_strokes.system());
+        let start = Instant::now();
+        for _ in 0..frames {
+            stage.run(&mut world);
+            //Every frame is a new stroke, like when the user lets go of the mouse between them
+            world
+                .get_resource_mut::<CurrentStroke>()
+                .unwrap()
+                .tiles_before
+                .clear();
+            world.clear_trackers();
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{}x{} tiles: {} stamps in {:?}, {:?} per stamp",
+            tileset_size,
+            tileset_size,
+            stamps,
+            elapsed,
+            elapsed / stamps.max(1)
+        );
+    }
+    Ok(())
+}
+///Spawns a selected tileset and it's blank tiles, with only the components the brush uses
+fn spawn_benchmark_tileset(world: &mut World, tileset_settings: TileSetSettings) {
+    let tile_settings = TileSettings {
+        tile_width: tileset_settings.tile_width,
+        tile_height: tileset_settings.tile_height,
+    };
+    let mut tileset_tiles = TileSetTiles::default();
+    for y in 0..tileset_settings.tileset_height {
+        for x in 0..tileset_settings.tileset_width {
+            tileset_tiles.tiles.push(
+                world
+                    .spawn()
+                    .insert_bundle((
+                        tile_settings,
+                        TilePosition {
+                            position: UVec2::new(x as u32, y as u32),
+                        },
+                        TileData::default(),
+                        TileTexture::default(),
+                    ))
+                    .id(),
+            );
+        }
+    }
+    world
+        .spawn()
+        .insert_bundle((tileset_settings, tileset_tiles, CurrentlySelected));
+}
+///Returns a stroke along the middle of each row of tiles and one along each of the tileset's diagonals
+fn get_benchmark_strokes(tileset_settings: &TileSetSettings) -> BenchmarkStrokes {
+    let width = (tileset_settings.tileset_width * tileset_settings.tile_width) as i32;
+    let height = (tileset_settings.tileset_height * tileset_settings.tile_height) as i32;
+    let mut pixels = Vec::new();
+    for row in 0..tileset_settings.tileset_height as i32 {
+        let y = row * tileset_settings.tile_height as i32 + tileset_settings.tile_height as i32 / 2;
+        pixels.extend(get_line_pixels(IVec2::new(0, y), IVec2::new(width - 1, y)));
+    }
+    pixels.extend(get_line_pixels(
+        IVec2::ZERO,
+        IVec2::new(width - 1, height - 1),
+    ));
+    pixels.extend(get_line_pixels(
+        IVec2::new(0, height - 1),
+        IVec2::new(width - 1, 0),
+    ));
+    BenchmarkStrokes { pixels }
+}
+///A round pattern a few pixels wide, so the stamps near the tiles' edges touch several tiles
+fn get_benchmark_pattern() -> Pattern {
+    let size = 5;
+    let radius = size as f32 / 2.0;
+    let mut pattern = Pattern::new(
+        (0..size * size)
+            .map(|index| {
+                let pixel = Vec2::new((index % size) as f32 + 0.5, (index / size) as f32 + 0.5);
+                if pixel.distance(Vec2::splat(radius)) <= radius {
+                    [255; 4]
+                } else {
+                    [0; 4]
+                }
+            })
+            .collect(),
+    );
+    pattern.size = UVec2::new(size as u32, size as u32);
+    pattern
+}
+///Stamps the pattern on every pixel of the strokes, the same way the brush does
+fn paint_benchmark_strokes(
+    strokes: Res<BenchmarkStrokes>,
+    pattern: Res<Pattern>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    mut query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    let tileset = tileset_query.single().unwrap();
+    for pixel in strokes.pixels.iter() {
+        stamp_pattern(
+            &pattern,
+            BrushOperation::Paint {
+                color: [255, 0, 0, 255],
+            },
+            *pixel,
+            None,
+            tileset,
+            &mut current_stroke,
+            &mut query,
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{can_draw_on_active_layer, get_just_pressed_drawing_button};
+
+use crate::data::{
+    history::CurrentStroke,
+    layers::TileSetLayers,
+    resources::{DrawingColors, FillMode, FillSettings, MousePixelPosition, SelectedTool},
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TilePosition, TileSettings, TileTexture},
+    tileset_entity::{TileSetSettings, TileSetTiles},
+};
+
+///Fills the pixels connected to the clicked pixel that have the same color, or every pixel with that color
+pub fn fill_tool(
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    tool: Res<SelectedTool>,
+    fill_settings: Res<FillSettings>,
+    drawing_colors: Res<DrawingColors>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    layers_query: Query<&TileSetLayers, With<CurrentlySelected>>,
+    mut tile_query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    let button = match get_just_pressed_drawing_button(&mouse_input) {
+        Some(button)
+            if matches!(*tool, SelectedTool::Fill) && can_draw_on_active_layer(&layers_query) =>
+        {
+            button
+        }
+        _ => return,
+    };
+    let color = drawing_colors.get(button);
+    if let (Some(mouse_pixel), Ok(tileset)) =
+        (mouse_pixel_position.pixel_position, tileset_query.single())
+    {
+        let tile_masks = flood_tiles(
+            tileset,
+            mouse_pixel.as_i32(),
+            fill_settings.mode != FillMode::Global,
+            //Only the clicked tile's pixels can be filled in tile mode
+            fill_settings.mode != FillMode::Tile,
+            fill_settings.tolerance,
+            |tile_entity| tile_query.get_component::<TileData>(tile_entity).ok(),
+        );
+        //Writing the color to every tile that has filled pixels
+        for tile_mask in tile_masks {
+            if let Ok((entity, tile_settings, _, mut tile_data, mut tile_texture)) =
+                tile_query.get_mut(tile_mask.tile_entity)
+            {
+                current_stroke.record(entity, &tile_data);
+                for pixel in tile_mask.filled_pixels(tile_settings) {
+                    tile_data.set_pixel(tile_settings, pixel, color);
+                    tile_texture.mark_dirty(tile_settings, pixel);
+                }
+            }
+        }
+    }
+}
+///The pixels of a single tile that a flood reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileFillMask {
+    pub tile_entity: Entity,
+    ///Where the tile is in it's tileset, counted in tiles like the [TilePosition](TilePosition)
+    pub tile_position: IVec2,
+    ///One per pixel, row by row from the tile's bottom row up, like the pixel positions of [TileData](TileData)
+    pub mask: Vec<bool>,
+}
+impl TileFillMask {
+    ///Returns the reached pixels' positions in the tile
+    pub fn filled_pixels<'a>(
+        &'a self,
+        tile_settings: &'a TileSettings,
+    ) -> impl Iterator<Item = UVec2> + 'a {
+        self.mask
+            .iter()
+            .enumerate()
+            .filter(|(_, filled)| **filled)
+            .map(move |(index, _)| {
+                UVec2::new(
+                    (index % tile_settings.tile_width) as u32,
+                    (index / tile_settings.tile_width) as u32,
+                )
+            })
+    }
+}
+///Returns the pixels a flood starting from a tileset pixel reaches, with a mask for every tile it reaches
+///
+///The flood goes through one tile at a time, when it crosses a tile's edge it continues in the tile next to it, found through the [TileSetTiles](TileSetTiles).
+///Only the start pixel's tile is flooded if cross_tiles is false.
+///If contiguous is false every pixel with the start pixel's color is reached, not just the ones connected to it
+pub fn flood_tiles<'a>(
+    (tileset_settings, tileset_tiles): (&TileSetSettings, &TileSetTiles),
+    start_pixel: IVec2,
+    contiguous: bool,
+    cross_tiles: bool,
+    tolerance: u8,
+    get_tile_data: impl Fn(Entity) -> Option<&'a TileData>,
+) -> Vec<TileFillMask> {
+    let tile_settings = TileSettings {
+        tile_width: tileset_settings.tile_width,
+        tile_height: tileset_settings.tile_height,
+    };
+    let tile_size = IVec2::new(
+        tileset_settings.tile_width as i32,
+        tileset_settings.tile_height as i32,
+    );
+    let get_tile = |tile_position: IVec2| {
+        let tile_entity = tileset_tiles.get(tileset_settings, tile_position)?;
+        Some((tile_entity, get_tile_data(tile_entity)?))
+    };
+    let start_tile = IVec2::new(
+        start_pixel.x.div_euclid(tile_size.x),
+        start_pixel.y.div_euclid(tile_size.y),
+    );
+    let target_color = match get_tile(start_tile) {
+        Some((_, tile_data)) => tile_data.get_pixel(
+            &tile_settings,
+            (start_pixel - start_tile * tile_size).as_u32(),
+        ),
+        None => return Vec::new(),
+    };
+    let is_target = |tile_data: &TileData, pixel: IVec2| {
+        colors_match(
+            tile_data.get_pixel(&tile_settings, pixel.as_u32()),
+            target_color,
+            tolerance,
+        )
+    };
+    let pixel_index = |pixel: IVec2| (pixel.y * tile_size.x + pixel.x) as usize;
+    let mut tile_masks = Vec::new();
+    if !contiguous {
+        let tile_positions: Vec<IVec2> = if cross_tiles {
+            (0..tileset_settings.tileset_height as i32)
+                .flat_map(|y| {
+                    (0..tileset_settings.tileset_width as i32).map(move |x| IVec2::new(x, y))
+                })
+                .collect()
+        } else {
+            vec![start_tile]
+        };
+        for tile_position in tile_positions {
+            if let Some((tile_entity, tile_data)) = get_tile(tile_position) {
+                let mask: Vec<bool> = (0..tile_size.y)
+                    .flat_map(|y| (0..tile_size.x).map(move |x| IVec2::new(x, y)))
+                    .map(|pixel| is_target(tile_data, pixel))
+                    .collect();
+                if mask.contains(&true) {
+                    tile_masks.push(TileFillMask {
+                        tile_entity,
+                        tile_position,
+                        mask,
+                    });
+                }
+            }
+        }
+        return tile_masks;
+    }
+    //Where each reached tile's mask is in the tile masks
+    let mut mask_indices: HashMap<IVec2, usize> = HashMap::default();
+    //The pixels the flood continues from once it's done with the tile it's in, as a tile and a pixel in it
+    let mut seeds = vec![(start_tile, start_pixel - start_tile * tile_size)];
+    while let Some((tile_position, seed)) = seeds.pop() {
+        let (tile_entity, tile_data) = match get_tile(tile_position) {
+            Some(tile) => tile,
+            None => continue,
+        };
+        let mask_index = mask_indices.get(&tile_position).copied();
+        if mask_index.map_or(false, |mask_index| {
+            tile_masks[mask_index].mask[pixel_index(seed)]
+        }) || !is_target(tile_data, seed)
+        {
+            continue;
+        }
+        //Tiles get their mask when the flood reaches them
+        let mask_index = mask_index.unwrap_or_else(|| {
+            tile_masks.push(TileFillMask {
+                tile_entity,
+                tile_position,
+                mask: vec![false; (tile_size.x * tile_size.y) as usize],
+            });
+            mask_indices.insert(tile_position, tile_masks.len() - 1);
+            tile_masks.len() - 1
+        });
+        let mask = &mut tile_masks[mask_index].mask;
+        //A scanline would be faster, but a stack of pixels is good enough for single clicks
+        mask[pixel_index(seed)] = true;
+        let mut stack = vec![seed];
+        while let Some(pixel) = stack.pop() {
+            for direction in [
+                IVec2::new(1, 0),
+                IVec2::new(-1, 0),
+                IVec2::new(0, 1),
+                IVec2::new(0, -1),
+            ]
+            .iter()
+            {
+                let neighbour = pixel + *direction;
+                if neighbour.x < 0
+                    || neighbour.y < 0
+                    || neighbour.x >= tile_size.x
+                    || neighbour.y >= tile_size.y
+                {
+                    //The neighbour is in the next tile, the flood gets there after this tile
+                    if cross_tiles {
+                        seeds.push((
+                            tile_position + *direction,
+                            neighbour - *direction * tile_size,
+                        ));
+                    }
+                    continue;
+                }
+                if !mask[pixel_index(neighbour)] && is_target(tile_data, neighbour) {
+                    mask[pixel_index(neighbour)] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+    }
+    tile_masks
+}
+///Checks whether every channel of two colors is within the tolerance of each other
+pub fn colors_match(a: [u8; 4], b: [u8; 4], tolerance: u8) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .all(|(a, b)| (*a as i16 - *b as i16).abs() <= tolerance as i16)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    ///Two 3x2 tiles next to each other
+    const TILESET_SETTINGS: TileSetSettings = TileSetSettings {
+        tile_width: 3,
+        tile_height: 2,
+        tileset_width: 2,
+        tileset_height: 1,
+    };
+    const TILE_SETTINGS: TileSettings = TileSettings {
+        tile_width: 3,
+        tile_height: 2,
+    };
+
+    fn get_tileset_tiles() -> TileSetTiles {
+        TileSetTiles {
+            tiles: vec![Entity::new(0), Entity::new(1)],
+        }
+    }
+
+    fn tile_with_red_pixels(pixels: &[(u32, u32)]) -> TileData {
+        let mut tile_data = TileData::default();
+        for (x, y) in pixels.iter() {
+            tile_data.set_pixel(&TILE_SETTINGS, UVec2::new(*x, *y), RED);
+        }
+        tile_data
+    }
+
+    fn flood(
+        tiles: &[TileData],
+        start_pixel: IVec2,
+        contiguous: bool,
+        cross_tiles: bool,
+    ) -> Vec<TileFillMask> {
+        let mut tile_masks = flood_tiles(
+            (&TILESET_SETTINGS, &get_tileset_tiles()),
+            start_pixel,
+            contiguous,
+            cross_tiles,
+            0,
+            |tile_entity| tiles.get(tile_entity.id() as usize),
+        );
+        tile_masks.sort_by_key(|tile_mask| tile_mask.tile_entity.id());
+        tile_masks
+    }
+
+    fn filled_pixels(tile_mask: &TileFillMask) -> Vec<UVec2> {
+        tile_mask.filled_pixels(&TILE_SETTINGS).collect()
+    }
+
+    #[test]
+    fn contiguous_floods_continue_into_the_next_tile() {
+        let tiles = [TileData::default(), TileData::default()];
+        let tile_masks = flood(&tiles, IVec2::new(0, 0), true, true);
+        assert_eq!(tile_masks.len(), 2);
+        assert_eq!(tile_masks[1].tile_position, IVec2::new(1, 0));
+        assert!(tile_masks
+            .iter()
+            .all(|tile_mask| tile_mask.mask.iter().all(|filled| *filled)));
+    }
+
+    #[test]
+    fn tile_floods_stay_in_the_clicked_tile() {
+        let tiles = [TileData::default(), TileData::default()];
+        let tile_masks = flood(&tiles, IVec2::new(4, 1), true, false);
+        assert_eq!(tile_masks.len(), 1);
+        assert_eq!(tile_masks[0].tile_entity, Entity::new(1));
+        assert_eq!(filled_pixels(&tile_masks[0]).len(), 6);
+    }
+
+    #[test]
+    fn floods_stop_at_other_colors() {
+        //A red column in the middle of the first tile walls the left column off
+        let tiles = [tile_with_red_pixels(&[(1, 0), (1, 1)]), TileData::default()];
+        let tile_masks = flood(&tiles, IVec2::new(0, 1), true, true);
+        assert_eq!(tile_masks.len(), 1);
+        assert_eq!(
+            filled_pixels(&tile_masks[0]),
+            vec![UVec2::new(0, 0), UVec2::new(0, 1)]
+        );
+        //The red pixels only reach each other
+        let tile_masks = flood(&tiles, IVec2::new(1, 0), true, true);
+        assert_eq!(
+            filled_pixels(&tile_masks[0]),
+            vec![UVec2::new(1, 0), UVec2::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn floods_cross_tile_edges_where_the_color_continues() {
+        let tiles = [
+            tile_with_red_pixels(&[(0, 1), (1, 0), (2, 0)]),
+            tile_with_red_pixels(&[(0, 0), (1, 0), (2, 0)]),
+        ];
+        //The top row goes through both tiles, the first tile's bottom left pixel is walled off
+        let tile_masks = flood(&tiles, IVec2::new(5, 1), true, true);
+        assert_eq!(tile_masks.len(), 2);
+        assert_eq!(
+            filled_pixels(&tile_masks[0]),
+            vec![UVec2::new(1, 1), UVec2::new(2, 1)]
+        );
+        assert_eq!(
+            filled_pixels(&tile_masks[1]),
+            vec![UVec2::new(0, 1), UVec2::new(1, 1), UVec2::new(2, 1)]
+        );
+        //The bottom red pixels touch across the edge, the top left one is on it's own
+        let tile_masks = flood(&tiles, IVec2::new(2, 0), true, true);
+        assert_eq!(
+            filled_pixels(&tile_masks[0]),
+            vec![UVec2::new(1, 0), UVec2::new(2, 0)]
+        );
+        assert_eq!(filled_pixels(&tile_masks[1]).len(), 3);
+    }
+
+    #[test]
+    fn global_floods_reach_every_pixel_with_the_color() {
+        let tiles = [
+            tile_with_red_pixels(&[(0, 0), (2, 1)]),
+            tile_with_red_pixels(&[(1, 1)]),
+        ];
+        let tile_masks = flood(&tiles, IVec2::new(0, 0), false, true);
+        assert_eq!(tile_masks.len(), 2);
+        assert_eq!(
+            filled_pixels(&tile_masks[0]),
+            vec![UVec2::new(0, 0), UVec2::new(2, 1)]
+        );
+        assert_eq!(filled_pixels(&tile_masks[1]), vec![UVec2::new(1, 1)]);
+        //Tiles without the color don't get a mask
+        let tiles = [tile_with_red_pixels(&[(0, 0)]), TileData::default()];
+        assert_eq!(flood(&tiles, IVec2::new(0, 0), false, true).len(), 1);
+    }
+
+    #[test]
+    fn floods_outside_of_the_tileset_reach_nothing() {
+        let tiles = [TileData::default(), TileData::default()];
+        assert!(flood(&tiles, IVec2::new(6, 0), true, true).is_empty());
+        assert!(flood(&tiles, IVec2::new(-1, 0), true, true).is_empty());
+    }
+}
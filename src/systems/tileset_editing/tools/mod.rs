@@ -1,14 +1,27 @@
 use bevy::prelude::*;
 use bevy_common::input::resources::MouseWorldPosition;
 pub mod brush;
+#[cfg(feature = "bench")]
+pub mod brush_benchmark;
+pub mod fill;
+pub mod picker;
+pub mod rasterizing;
+pub mod selection;
+pub mod selection_mask;
+pub mod shapes;
 use crate::{
     data::{
-        resources::MousePixelPosition, shared_components::CurrentlySelected,
+        layers::TileSetLayers, resources::MousePixelPosition, shared_components::CurrentlySelected,
         tileset_entity::TileSetSettings,
     },
     systems::initializing::get_total_tileset_size_pixels,
 };
 pub use brush::*;
+pub use fill::*;
+pub use picker::*;
+pub use selection::*;
+pub use selection_mask::*;
+pub use shapes::*;
 
 ///This sets a resource that holds the mouse's pixel position for this frame, if it's not on a tileset, it's set to None
 pub fn get_mouse_pixel_tileset_pos(
@@ -35,3 +48,25 @@ pub fn get_mouse_pixel_tileset_pos(
         }
     }
 }
+///Returns the mouse button that draws with the tools if one is pressed, left comes before right
+pub fn get_pressed_drawing_button(mouse_input: &Input<MouseButton>) -> Option<MouseButton> {
+    [MouseButton::Left, MouseButton::Right]
+        .iter()
+        .copied()
+        .find(|button| mouse_input.pressed(*button))
+}
+///Returns the mouse button that draws with the tools if one was pressed this frame
+pub fn get_just_pressed_drawing_button(mouse_input: &Input<MouseButton>) -> Option<MouseButton> {
+    [MouseButton::Left, MouseButton::Right]
+        .iter()
+        .copied()
+        .find(|button| mouse_input.just_pressed(*button))
+}
+///Checks whether the tools can draw on the currently selected tileset's active layer, they can't draw on hidden or locked layers
+pub fn can_draw_on_active_layer(
+    layers_query: &Query<&TileSetLayers, With<CurrentlySelected>>,
+) -> bool {
+    layers_query.single().map_or(false, |tileset_layers| {
+        tileset_layers.is_active_layer_editable()
+    })
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use super::get_pressed_drawing_button;
+
+use crate::data::{
+    resources::{DrawingColors, MousePixelPosition, SelectedTool},
+    shared_components::CurrentlySelected,
+    tile_entity::{TileData, TilePosition, TileSettings},
+    tileset_entity::{TileSetSettings, TileSetTiles},
+};
+///Makes the color under the mouse the drawing color of the pressed button, while the picker is selected or alt is held with the pencil
+pub fn picker_tool(
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    tool: Res<SelectedTool>,
+    mut drawing_colors: ResMut<DrawingColors>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    query: Query<(&TileSettings, &TilePosition, &TileData)>,
+) {
+    let is_picking = match *tool {
+        SelectedTool::Picker => true,
+        SelectedTool::Pencil { .. } => is_alt_pressed(&keyboard_input),
+        _ => false,
+    };
+    //Picking with the right mouse button sets the secondary color
+    let button = match get_pressed_drawing_button(&mouse_input) {
+        Some(button) if is_picking => button,
+        _ => return,
+    };
+    if let (Some(mouse_pixel), Ok((tileset_settings, tileset_tiles))) =
+        (mouse_pixel_position.pixel_position, tileset_query.single())
+    {
+        //Only the hovered tile has the pixel
+        let hovered_tile = tileset_tiles
+            .get_at_pixel(tileset_settings, mouse_pixel.as_i32())
+            .and_then(|tile_entity| query.get(tile_entity).ok());
+        if let Some((tile_settings, tile_position, tile_data)) = hovered_tile {
+            let color = tile_data.get_pixel(
+                tile_settings,
+                mouse_pixel - tile_position.min_pixel(tile_settings),
+            );
+            //Only changing the resource when needed, so it isn't marked as changed every frame
+            if drawing_colors.get(button) != color {
+                *drawing_colors.get_mut(button) = color;
+            }
+        }
+    }
+}
+///Checks whether either alt key is held, which turns the pencil into the picker
+pub fn is_alt_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.pressed(KeyCode::LAlt) || keyboard_input.pressed(KeyCode::RAlt)
+}
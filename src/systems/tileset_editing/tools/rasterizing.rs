@@ -0,0 +1,318 @@
+use bevy::prelude::*;
+///Returns every pixel on the line between two pixels, including both of them, using Bresenham's line algorithm
+pub fn get_line_pixels(from: IVec2, to: IVec2) -> Vec<IVec2> {
+    let delta_x = (to.x - from.x).abs();
+    let delta_y = -(to.y - from.y).abs();
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let mut error = delta_x + delta_y;
+    let mut current = from;
+    let mut pixels = Vec::with_capacity(i32::max(delta_x, -delta_y) as usize + 1);
+    loop {
+        pixels.push(current);
+        if current == to {
+            break;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= delta_y {
+            error += delta_y;
+            current.x += step_x;
+        }
+        if doubled_error <= delta_x {
+            error += delta_x;
+            current.y += step_y;
+        }
+    }
+    pixels
+}
+///Checks whether the middle pixel makes an L shaped corner between the other two, which pixel perfect strokes skip
+pub fn is_l_shaped_corner(previous: IVec2, middle: IVec2, next: IVec2) -> bool {
+    let is_orthogonal_neighbour = |a: IVec2, b: IVec2| (a.x - b.x).abs() + (a.y - b.y).abs() == 1;
+    is_orthogonal_neighbour(previous, middle)
+        && is_orthogonal_neighbour(middle, next)
+        && (previous.x - next.x).abs() == 1
+        && (previous.y - next.y).abs() == 1
+}
+///Returns the pixels of the rectangle that has these two pixels as opposite corners
+pub fn get_rectangle_pixels(corner: IVec2, opposite_corner: IVec2, filled: bool) -> Vec<IVec2> {
+    let min = corner.min(opposite_corner);
+    let max = corner.max(opposite_corner);
+    let mut pixels = Vec::new();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            if filled || x == min.x || x == max.x || y == min.y || y == max.y {
+                pixels.push(IVec2::new(x, y));
+            }
+        }
+    }
+    pixels
+}
+///Returns the pixels of the ellipse that fits in the rectangle between two corner pixels
+///
+///A pixel is inside if it's center is, the outline is made of the inside pixels that have an outside neighbour
+pub fn get_ellipse_pixels(corner: IVec2, opposite_corner: IVec2, filled: bool) -> Vec<IVec2> {
+    let min = corner.min(opposite_corner);
+    let max = corner.max(opposite_corner);
+    let center = (min + max + IVec2::ONE).as_f32() / 2.0;
+    let radii = (max - min + IVec2::ONE).as_f32() / 2.0;
+    let is_inside = |pixel: IVec2| {
+        let offset = (pixel.as_f32() + Vec2::splat(0.5) - center) / radii;
+        offset.length_squared() <= 1.0
+    };
+    let mut pixels = Vec::new();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let pixel = IVec2::new(x, y);
+            if !is_inside(pixel) {
+                continue;
+            }
+            if filled
+                || !is_inside(pixel + IVec2::X)
+                || !is_inside(pixel - IVec2::X)
+                || !is_inside(pixel + IVec2::Y)
+                || !is_inside(pixel - IVec2::Y)
+            {
+                pixels.push(pixel);
+            }
+        }
+    }
+    pixels
+}
+///Moves a line's end so the line is horizontal, vertical or diagonal, whichever is closest
+pub fn constrain_line_end(start: IVec2, end: IVec2) -> IVec2 {
+    let delta = end - start;
+    let (length_x, length_y) = (delta.x.abs(), delta.y.abs());
+    if length_x > length_y * 2 {
+        IVec2::new(end.x, start.y)
+    } else if length_y > length_x * 2 {
+        IVec2::new(start.x, end.y)
+    } else {
+        let length = i32::max(length_x, length_y);
+        start + IVec2::new(delta.x.signum() * length, delta.y.signum() * length)
+    }
+}
+///Moves the opposite corner of a rectangle so all of it's sides have the same length
+pub fn constrain_to_square(corner: IVec2, opposite_corner: IVec2) -> IVec2 {
+    let delta = opposite_corner - corner;
+    let length = i32::max(delta.x.abs(), delta.y.abs());
+    //Dragging straight along an axis still grows the square in a direction
+    let signum = |value: i32| if value < 0 { -1 } else { 1 };
+    corner + IVec2::new(signum(delta.x) * length, signum(delta.y) * length)
+}
+///Returns the pixels of a closed polygon going through the vertices, filled, with it's outline included
+///
+///The inside is found row by row with the even odd rule, so shapes that cross themselves get holes
+pub fn get_polygon_pixels(vertices: &[IVec2]) -> Vec<IVec2> {
+    let mut pixels = Vec::new();
+    if vertices.is_empty() {
+        return pixels;
+    }
+    //The outline makes sure thin shapes still have their pixels
+    for (index, vertex) in vertices.iter().enumerate() {
+        let next_vertex = vertices[(index + 1) % vertices.len()];
+        pixels.extend(get_line_pixels(*vertex, next_vertex));
+    }
+    let min_y = vertices.iter().map(|vertex| vertex.y).min().unwrap_or(0);
+    let max_y = vertices.iter().map(|vertex| vertex.y).max().unwrap_or(0);
+    let mut crossings = Vec::new();
+    for y in min_y..=max_y {
+        //Where the row crosses the polygon's edges, between each pair of crossings is inside
+        crossings.clear();
+        for (index, vertex) in vertices.iter().enumerate() {
+            let next_vertex = vertices[(index + 1) % vertices.len()];
+            if (vertex.y <= y) != (next_vertex.y <= y) {
+                let t = (y - vertex.y) as f32 / (next_vertex.y - vertex.y) as f32;
+                crossings.push(vertex.x as f32 + t * (next_vertex.x - vertex.x) as f32);
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in crossings.chunks_exact(2) {
+            for x in pair[0].ceil() as i32..=pair[1].floor() as i32 {
+                pixels.push(IVec2::new(x, y));
+            }
+        }
+    }
+    pixels
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn pixels(coordinates: &[(i32, i32)]) -> Vec<IVec2> {
+        coordinates
+            .iter()
+            .map(|(x, y)| IVec2::new(*x, *y))
+            .collect()
+    }
+    fn pixel_set(pixels: Vec<IVec2>) -> HashSet<(i32, i32)> {
+        pixels.iter().map(|pixel| (pixel.x, pixel.y)).collect()
+    }
+    ///The ends of the eight octants' lines, with the axes and the diagonals between them
+    const LINE_ENDS: [(i32, i32); 16] = [
+        (5, 2),
+        (2, 5),
+        (-2, 5),
+        (-5, 2),
+        (-5, -2),
+        (-2, -5),
+        (2, -5),
+        (5, -2),
+        (5, 0),
+        (0, 5),
+        (-5, 0),
+        (0, -5),
+        (5, 5),
+        (-5, 5),
+        (-5, -5),
+        (5, -5),
+    ];
+    #[test]
+    fn lines_connect_their_ends_in_every_octant() {
+        let from = IVec2::new(1, -1);
+        for (x, y) in LINE_ENDS.iter() {
+            let to = from + IVec2::new(*x, *y);
+            let line = get_line_pixels(from, to);
+            assert_eq!(line.first(), Some(&from));
+            assert_eq!(line.last(), Some(&to));
+            //A pixel for every step along the longer axis, each one touching the one before it
+            assert_eq!(line.len() as i32, i32::max(x.abs(), y.abs()) + 1);
+            for step in line.windows(2) {
+                let delta = step[1] - step[0];
+                assert_eq!(i32::max(delta.x.abs(), delta.y.abs()), 1);
+                //Lines never step back
+                assert!(delta.x * x.signum() >= 0);
+                assert!(delta.y * y.signum() >= 0);
+            }
+        }
+    }
+    #[test]
+    fn shallow_and_steep_lines_have_the_expected_pixels() {
+        assert_eq!(
+            get_line_pixels(IVec2::ZERO, IVec2::new(4, 2)),
+            pixels(&[(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)])
+        );
+        assert_eq!(
+            get_line_pixels(IVec2::ZERO, IVec2::new(2, 4)),
+            pixels(&[(0, 0), (1, 1), (1, 2), (2, 3), (2, 4)])
+        );
+        assert_eq!(
+            get_line_pixels(IVec2::ZERO, IVec2::new(-4, -2)),
+            pixels(&[(0, 0), (-1, -1), (-2, -1), (-3, -2), (-4, -2)])
+        );
+    }
+    #[test]
+    fn zero_length_lines_have_a_single_pixel() {
+        let pixel = IVec2::new(3, -7);
+        assert_eq!(get_line_pixels(pixel, pixel), vec![pixel]);
+    }
+    #[test]
+    fn l_shaped_corners_are_found() {
+        let corner = |a: (i32, i32), b: (i32, i32), c: (i32, i32)| {
+            is_l_shaped_corner(
+                IVec2::new(a.0, a.1),
+                IVec2::new(b.0, b.1),
+                IVec2::new(c.0, c.1),
+            )
+        };
+        assert!(corner((0, 0), (1, 0), (1, 1)));
+        assert!(corner((0, 0), (0, 1), (1, 1)));
+        assert!(corner((0, 0), (-1, 0), (-1, -1)));
+        //Straight and diagonal steps aren't corners
+        assert!(!corner((0, 0), (1, 0), (2, 0)));
+        assert!(!corner((0, 0), (1, 1), (2, 2)));
+        assert!(!corner((0, 0), (1, 0), (2, 1)));
+        //Going back isn't a corner either
+        assert!(!corner((0, 0), (1, 0), (0, 0)));
+    }
+    #[test]
+    fn rectangles_have_their_outline_or_every_pixel() {
+        let corner = IVec2::new(2, 1);
+        let opposite_corner = IVec2::new(0, 3);
+        assert_eq!(get_rectangle_pixels(corner, opposite_corner, true).len(), 9);
+        let outline = pixel_set(get_rectangle_pixels(corner, opposite_corner, false));
+        assert_eq!(outline.len(), 8);
+        assert!(!outline.contains(&(1, 2)));
+        //The corners can be given either way around
+        assert_eq!(
+            outline,
+            pixel_set(get_rectangle_pixels(opposite_corner, corner, false))
+        );
+    }
+    #[test]
+    fn single_pixel_and_one_pixel_wide_rectangles() {
+        let pixel = IVec2::new(4, 4);
+        assert_eq!(get_rectangle_pixels(pixel, pixel, false), vec![pixel]);
+        assert_eq!(get_rectangle_pixels(pixel, pixel, true), vec![pixel]);
+        assert_eq!(
+            get_rectangle_pixels(pixel, pixel + IVec2::new(0, 3), false).len(),
+            4
+        );
+    }
+    #[test]
+    fn ellipses_are_round_and_their_outline_is_on_the_inside() {
+        let corner = IVec2::ZERO;
+        let opposite_corner = IVec2::new(4, 4);
+        let filled = pixel_set(get_ellipse_pixels(corner, opposite_corner, true));
+        assert_eq!(filled.len(), 21);
+        //The corners are outside of the circle
+        for corner_pixel in [(0, 0), (4, 0), (0, 4), (4, 4)].iter() {
+            assert!(!filled.contains(corner_pixel));
+        }
+        let outline = pixel_set(get_ellipse_pixels(corner, opposite_corner, false));
+        assert_eq!(
+            outline,
+            pixel_set(pixels(&[
+                (1, 0),
+                (2, 0),
+                (3, 0),
+                (0, 1),
+                (4, 1),
+                (0, 2),
+                (4, 2),
+                (0, 3),
+                (4, 3),
+                (1, 4),
+                (2, 4),
+                (3, 4)
+            ]))
+        );
+        assert!(outline.is_subset(&filled));
+    }
+    #[test]
+    fn single_pixel_ellipses_have_that_pixel() {
+        let pixel = IVec2::new(2, 3);
+        assert_eq!(get_ellipse_pixels(pixel, pixel, false), vec![pixel]);
+        assert_eq!(get_ellipse_pixels(pixel, pixel, true), vec![pixel]);
+    }
+    #[test]
+    fn polygons_are_filled_with_their_outline() {
+        let square = pixels(&[(0, 0), (3, 0), (3, 3), (0, 3)]);
+        let square_pixels = pixel_set(get_polygon_pixels(&square));
+        assert_eq!(square_pixels.len(), 16);
+        let triangle = pixels(&[(0, 0), (6, 0), (0, 6)]);
+        let triangle_pixels = pixel_set(get_polygon_pixels(&triangle));
+        assert!(triangle_pixels.contains(&(1, 1)));
+        assert!(triangle_pixels.contains(&(3, 3)));
+        assert!(!triangle_pixels.contains(&(5, 5)));
+    }
+    #[test]
+    fn degenerate_polygons() {
+        assert!(get_polygon_pixels(&[]).is_empty());
+        let pixel = IVec2::new(5, 5);
+        assert_eq!(
+            pixel_set(get_polygon_pixels(&[pixel])),
+            pixel_set(vec![pixel])
+        );
+        //Two vertices are a line there and back
+        let from = IVec2::ZERO;
+        let to = IVec2::new(4, 2);
+        assert_eq!(
+            pixel_set(get_polygon_pixels(&[from, to])),
+            pixel_set(get_line_pixels(from, to))
+                .union(&pixel_set(get_line_pixels(to, from)))
+                .copied()
+                .collect()
+        );
+    }
+}
@@ -0,0 +1,668 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::Indices,
+        pipeline::{PrimitiveTopology, RenderPipeline},
+        texture::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_common::input::marker_components::MainCamera;
+use bevy_egui::EguiContext;
+
+use super::can_draw_on_active_layer;
+use crate::{
+    data::{
+        history::CurrentStroke,
+        layers::{blend_pixels, BlendMode, TileSetLayers},
+        resources::{MousePixelPosition, SelectedTool},
+        selection::{
+            Clipboard, FloatingPixels, MarchingAntsMaterial, PixelRect, Selection, SelectionMask,
+        },
+        shared_components::CurrentlySelected,
+        tile_entity::{TileData, TilePosition, TileSettings, TileTexture},
+        tileset_entity::{NewlySelected, TileSetSettings, TileSetTiles},
+    },
+    systems::initializing::get_total_tileset_size_pixels,
+};
+///What the select tool remembers between frames while the mouse is dragged
+#[derive(Debug, Default)]
+pub struct SelectDragState {
+    ///The pixel where a new selection's drag started
+    start_pixel: Option<IVec2>,
+    ///The pixel the selection was grabbed at, the selection moves along with the mouse
+    grab_pixel: Option<IVec2>,
+}
+///Drags a new selection, or moves the selection when it's dragged from inside of it
+///
+///Moving the selection lifts it's pixels out of the tiles, they float until they're put down
+pub fn select_tool(
+    mut drag_state: Local<SelectDragState>,
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    tool: Res<SelectedTool>,
+    mut selection: ResMut<Selection>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    layers_query: Query<&TileSetLayers, With<CurrentlySelected>>,
+    mut tile_query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    let tileset = match tileset_query.single() {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    let can_draw = can_draw_on_active_layer(&layers_query);
+    if !matches!(*tool, SelectedTool::Select) {
+        *drag_state = SelectDragState::default();
+        //The other tools draw on the tiles, so the floating pixels are put down first
+        if selection.floating.is_some() && can_draw {
+            put_down_floating_pixels(
+                &mut selection,
+                tileset,
+                &mut current_stroke,
+                &mut tile_query,
+            );
+        }
+        return;
+    }
+    let mouse_pixel = mouse_pixel_position
+        .pixel_position
+        .map(|mouse_pixel| mouse_pixel.as_i32());
+    if let (true, Some(mouse_pixel)) = (mouse_input.just_pressed(MouseButton::Left), mouse_pixel) {
+        match selection.rect {
+            Some(rect) if rect.contains(mouse_pixel) => {
+                //Locked and hidden layers keep their pixels
+                if selection.floating.is_none() {
+                    if !can_draw {
+                        return;
+                    }
+                    lift_selected_pixels(
+                        &mut selection,
+                        tileset,
+                        &mut current_stroke,
+                        &mut tile_query,
+                    );
+                }
+                drag_state.grab_pixel = Some(mouse_pixel);
+            }
+            _ => {
+                if selection.floating.is_some() {
+                    if !can_draw {
+                        return;
+                    }
+                    put_down_floating_pixels(
+                        &mut selection,
+                        tileset,
+                        &mut current_stroke,
+                        &mut tile_query,
+                    );
+                }
+                selection.rect = None;
+                drag_state.start_pixel = Some(mouse_pixel);
+            }
+        }
+    }
+    if mouse_input.pressed(MouseButton::Left) {
+        //The selection keeps it's last rect while the mouse is outside the tileset
+        if let Some(mouse_pixel) = mouse_pixel {
+            if let (Some(grab_pixel), Some(rect)) = (drag_state.grab_pixel, selection.rect) {
+                if mouse_pixel != grab_pixel {
+                    selection.rect = Some(rect.translated(mouse_pixel - grab_pixel));
+                    drag_state.grab_pixel = Some(mouse_pixel);
+                }
+            } else if let Some(start_pixel) = drag_state.start_pixel {
+                let rect = PixelRect::from_corners(start_pixel, mouse_pixel);
+                if selection.rect != Some(rect) {
+                    selection.rect = Some(rect);
+                }
+            }
+        }
+    } else {
+        //Clicking without dragging only deselects
+        if let (Some(start_pixel), Some(rect)) = (drag_state.start_pixel, selection.rect) {
+            if rect == PixelRect::from_corners(start_pixel, start_pixel) {
+                selection.rect = None;
+            }
+        }
+        *drag_state = SelectDragState::default();
+    }
+}
+///Ctrl+C copies the selected pixels, Ctrl+X cuts them and Ctrl+V pastes them as floating pixels
+///
+///Enter puts the floating pixels down, Escape also deselects and Delete erases the selected pixels
+pub fn selection_keyboard_shortcuts(
+    keyboard_input: Res<Input<KeyCode>>,
+    egui_context: Res<EguiContext>,
+    mut tool: ResMut<SelectedTool>,
+    mut selection: ResMut<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    layers_query: Query<&TileSetLayers, With<CurrentlySelected>>,
+    mut mask_query: Query<&mut SelectionMask, With<CurrentlySelected>>,
+    mut tile_query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    //Typing in the gui uses the same keys
+    if egui_context.ctx().wants_keyboard_input() {
+        return;
+    }
+    let tileset = match tileset_query.single() {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    let control =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let can_draw = can_draw_on_active_layer(&layers_query);
+    let copy = control && keyboard_input.just_pressed(KeyCode::C);
+    let cut = control && keyboard_input.just_pressed(KeyCode::X);
+    if let (true, Some(rect)) = (copy || cut, selection.rect) {
+        let pixels = match &selection.floating {
+            Some(floating) => floating.clone(),
+            None => copy_tileset_pixels(rect, tileset, &mut current_stroke, &mut tile_query),
+        };
+        clipboard.pixels = Some(pixels);
+        clipboard.position = rect.min;
+        //Floating pixels were already lifted out of the tiles
+        if cut && selection.floating.take().is_none() && can_draw {
+            erase_tileset_pixels(rect, tileset, &mut current_stroke, &mut tile_query);
+        }
+        if cut {
+            selection.rect = None;
+        }
+    }
+    if control && keyboard_input.just_pressed(KeyCode::V) && can_draw {
+        if let Some(pixels) = &clipboard.pixels {
+            let (tileset_settings, _) = tileset;
+            put_down_floating_pixels(
+                &mut selection,
+                tileset,
+                &mut current_stroke,
+                &mut tile_query,
+            );
+            //The pixels are pasted where they were copied from, moved inside if they were copied from a bigger tileset
+            let size = pixels.size.as_i32();
+            let tileset_size = get_total_tileset_size_pixels(tileset_settings).as_i32();
+            let position = clipboard.position.min(tileset_size - size).max(IVec2::ZERO);
+            selection.rect = Some(PixelRect {
+                min: position,
+                max: position + size,
+            });
+            selection.floating = Some(pixels.clone());
+            //The pasted pixels can be dragged right away
+            if !matches!(*tool, SelectedTool::Select) {
+                *tool = SelectedTool::Select;
+            }
+        }
+    }
+    //Deselecting clears the lasso and magic wand's mask too
+    if control && keyboard_input.just_pressed(KeyCode::D) {
+        if can_draw {
+            put_down_floating_pixels(
+                &mut selection,
+                tileset,
+                &mut current_stroke,
+                &mut tile_query,
+            );
+        }
+        //Floating pixels that couldn't be put down stay selected
+        if selection.floating.is_none() && selection.rect.is_some() {
+            selection.rect = None;
+        }
+        if let Ok(mut selection_mask) = mask_query.single_mut() {
+            if selection_mask.mask.is_some() {
+                selection_mask.mask = None;
+            }
+        }
+    }
+    if selection.rect.is_none() {
+        return;
+    }
+    if (keyboard_input.just_pressed(KeyCode::Return)
+        || keyboard_input.just_pressed(KeyCode::Escape))
+        && can_draw
+    {
+        put_down_floating_pixels(
+            &mut selection,
+            tileset,
+            &mut current_stroke,
+            &mut tile_query,
+        );
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            selection.rect = None;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Delete) {
+        if selection.floating.take().is_some() {
+            selection.rect = None;
+        } else if let (Some(rect), true) = (selection.rect, can_draw) {
+            erase_tileset_pixels(rect, tileset, &mut current_stroke, &mut tile_query);
+        }
+    }
+}
+///Puts the floating pixels down before another tileset is selected, the selection belongs to the tileset it was made on
+///
+///This runs before the selected tileset changes, so the floating pixels go in the right tiles and their stroke in the right history
+pub fn put_down_selection_before_switching_tilesets(
+    newly_selected_query: Query<(), With<NewlySelected>>,
+    mut selection: ResMut<Selection>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<(&TileSetSettings, &TileSetTiles), With<CurrentlySelected>>,
+    mut tile_query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    if newly_selected_query.iter().next().is_none() || selection.rect.is_none() {
+        return;
+    }
+    let tileset = match tileset_query.single() {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    put_down_floating_pixels(
+        &mut selection,
+        tileset,
+        &mut current_stroke,
+        &mut tile_query,
+    );
+    selection.rect = None;
+}
+///Moves the selected pixels of the active layer out of the tiles and into the selection's floating pixels
+fn lift_selected_pixels(
+    selection: &mut Selection,
+    tileset: (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    if let Some(rect) = selection.rect {
+        selection.floating = Some(copy_tileset_pixels(rect, tileset, current_stroke, query));
+        erase_tileset_pixels(rect, tileset, current_stroke, query);
+    }
+}
+///Puts the floating pixels over the pixels under the selection, the selection stays without them
+pub fn put_down_floating_pixels(
+    selection: &mut Selection,
+    tileset: (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    if let (Some(rect), Some(floating)) = (selection.rect, selection.floating.take()) {
+        update_tileset_pixels_in_rect(rect, tileset, current_stroke, query, |pixel, color| {
+            blend_pixels(color, floating.get_pixel(pixel), BlendMode::Normal, u8::MAX)
+        });
+    }
+}
+///Copies the active layer's pixels in a rectangle of the tileset, pixels outside of the tileset are transparent
+pub fn copy_tileset_pixels(
+    rect: PixelRect,
+    tileset: (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) -> FloatingPixels {
+    let size = rect.size().as_u32();
+    let mut copied = FloatingPixels {
+        pixels: vec![0; (size.x * size.y * 4) as usize],
+        size,
+    };
+    update_tileset_pixels_in_rect(rect, tileset, current_stroke, query, |pixel, color| {
+        let index = copied.pixel_index(pixel);
+        copied.pixels[index..index + 4].copy_from_slice(&color);
+        color
+    });
+    copied
+}
+///Makes the active layer's pixels in a rectangle of the tileset transparent
+pub fn erase_tileset_pixels(
+    rect: PixelRect,
+    tileset: (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    update_tileset_pixels_in_rect(rect, tileset, current_stroke, query, |_, _| [0; 4]);
+}
+///Calls a function with every pixel of the active layer in a rectangle of the tileset and it's position in the rectangle
+///
+///Pixels the function returns a different color for are changed in their tile, pixels outside of the tileset are skipped
+fn update_tileset_pixels_in_rect(
+    rect: PixelRect,
+    tileset: (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+    mut update_pixel: impl FnMut(UVec2, [u8; 4]) -> [u8; 4],
+) {
+    let (tileset_settings, tileset_tiles) = tileset;
+    //Only the tiles under the rectangle are looked at
+    for tile_entity in tileset_tiles.get_overlapped(tileset_settings, rect.min, rect.max) {
+        let (entity, tile_settings, tile_position, mut tile_data, mut tile_texture) =
+            match query.get_mut(tile_entity) {
+                Ok(tile) => tile,
+                Err(_) => continue,
+            };
+        let tile_min_pixel = tile_position.min_pixel(tile_settings).as_i32();
+        let tile_max_pixel = tile_min_pixel
+            + IVec2::new(
+                tile_settings.tile_width as i32,
+                tile_settings.tile_height as i32,
+            );
+        let min = rect.min.max(tile_min_pixel);
+        let max = rect.max.min(tile_max_pixel);
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let pixel = IVec2::new(x, y);
+                let pixel_in_tile = (pixel - tile_min_pixel).as_u32();
+                let color = tile_data.get_pixel(tile_settings, pixel_in_tile);
+                let new_color = update_pixel((pixel - rect.min).as_u32(), color);
+                //Only touching the tile when it really changes, so copying doesn't update it's texture
+                if new_color != color {
+                    current_stroke.record(entity, &tile_data);
+                    tile_data.set_pixel(tile_settings, pixel_in_tile, new_color);
+                    tile_texture.mark_dirty(tile_settings, pixel_in_tile);
+                }
+            }
+        }
+    }
+}
+///How many pixels each black or white dash of a selection's outline is
+const MARCHING_ANTS_DASH_LENGTH: i32 = 2;
+///How long the dashes wait before moving a pixel
+const MARCHING_ANTS_STEP_SECONDS: f64 = 0.15;
+///How wide a selection's outline is, in screen pixels
+const MARCHING_ANTS_WIDTH: f32 = 2.0;
+///A pixel long piece of a selection's outline between two pixel corners, and how far along the outline it is
+pub type OutlineSegment = (IVec2, IVec2, i32);
+///The entities that show the selection, kept between frames
+#[derive(Debug, Default)]
+pub struct SelectionVisuals {
+    ants: MarchingAnts,
+    ///The rect and the tileset the outline was built for, so it's only built again when one of them changes
+    outlined: Option<(PixelRect, Entity)>,
+    floating_sprite: Option<Entity>,
+    ///The floating pixels' material is reused while they're only moved
+    floating_material: Option<(FloatingPixels, Handle<ColorMaterial>)>,
+}
+///Shows the selection's outline as marching ants, and the floating pixels over the tiles
+pub fn show_selection(
+    mut commands: Commands,
+    mut visuals: Local<SelectionVisuals>,
+    time: Res<Time>,
+    selection: Res<Selection>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ants_materials: ResMut<Assets<MarchingAntsMaterial>>,
+    tileset_query: Query<(Entity, &TileSetSettings, &GlobalTransform), With<CurrentlySelected>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let (rect, (tileset_entity, tileset_settings, global_transform), camera_transform) = match (
+        selection.rect,
+        tileset_query.single(),
+        camera_query.single(),
+    ) {
+        (Some(rect), Ok(tileset), Ok(camera_transform)) => (rect, tileset, camera_transform),
+        _ => {
+            visuals.ants.despawn(&mut commands);
+            visuals.outlined = None;
+            if let Some(floating_sprite) = visuals.floating_sprite.take() {
+                commands.entity(floating_sprite).despawn();
+            }
+            visuals.floating_material = None;
+            return;
+        }
+    };
+    let outlined = Some((rect, tileset_entity));
+    if visuals.outlined != outlined {
+        visuals.ants.set_outline(
+            &mut commands,
+            (&mut meshes, &mut ants_materials),
+            &get_rect_outline(rect),
+            get_tileset_pixels_transform(tileset_settings, global_transform, 11.0),
+        );
+        visuals.outlined = outlined;
+    }
+    visuals.ants.update(
+        &mut ants_materials,
+        get_marching_ants_phase(&time),
+        get_marching_ants_width(camera_transform, global_transform),
+    );
+    if !selection.is_changed() && visuals.floating_sprite.is_some() {
+        return;
+    }
+    if let Some(floating_sprite) = visuals.floating_sprite.take() {
+        commands.entity(floating_sprite).despawn();
+    }
+    let floating = match &selection.floating {
+        Some(floating) => floating,
+        None => {
+            visuals.floating_material = None;
+            return;
+        }
+    };
+    let material_handle = match &visuals.floating_material {
+        Some((shown_floating, material_handle)) if shown_floating == floating => {
+            material_handle.clone()
+        }
+        _ => {
+            let texture_handle = textures.add(Texture::new(
+                Extent3d::new(floating.size.x, floating.size.y, 1),
+                TextureDimension::D2,
+                floating.pixels.clone(),
+                TextureFormat::Rgba8UnormSrgb,
+            ));
+            let material_handle = materials.add(ColorMaterial::texture(texture_handle));
+            visuals.floating_material = Some((floating.clone(), material_handle.clone()));
+            material_handle
+        }
+    };
+    //Sprites are placed by their center
+    let mut transform = get_tileset_pixels_transform(tileset_settings, global_transform, 10.0);
+    transform.translation +=
+        (rect.min.as_f32() + rect.size().as_f32() / 2.0).extend(0.0) * global_transform.scale;
+    visuals.floating_sprite = Some(
+        commands
+            .spawn_bundle(SpriteBundle {
+                material: material_handle,
+                transform,
+                ..Default::default()
+            })
+            .id(),
+    );
+}
+///Returns a transform for things built in tileset pixels, it puts them on top of the tileset like the tiles
+pub fn get_tileset_pixels_transform(
+    tileset_settings: &TileSetSettings,
+    global_transform: &GlobalTransform,
+    z: f32,
+) -> Transform {
+    let tileset_size = get_total_tileset_size_pixels(tileset_settings);
+    Transform {
+        translation: (-tileset_size / 2.0 * global_transform.scale.truncate()).extend(z),
+        scale: global_transform.scale,
+        ..Default::default()
+    }
+}
+///Returns the width of a selection's outline in tileset pixels, so it keeps the same width on screen however zoomed in the camera is
+pub fn get_marching_ants_width(
+    camera_transform: &Transform,
+    global_transform: &GlobalTransform,
+) -> f32 {
+    MARCHING_ANTS_WIDTH * camera_transform.scale.x / global_transform.scale.x
+}
+///Returns how far the dashes of the selections' outlines moved, it goes back to 0 when they look the same as at the start
+pub fn get_marching_ants_phase(time: &Time) -> i32 {
+    ((time.seconds_since_startup() / MARCHING_ANTS_STEP_SECONDS) as i64
+        % (MARCHING_ANTS_DASH_LENGTH * 2) as i64) as i32
+}
+///Returns the outline going around a rect's edges a pixel at a time
+fn get_rect_outline(rect: PixelRect) -> Vec<OutlineSegment> {
+    let corners = [
+        rect.min,
+        IVec2::new(rect.max.x, rect.min.y),
+        rect.max,
+        IVec2::new(rect.min.x, rect.max.y),
+    ];
+    let mut segments = Vec::new();
+    for (index, start) in corners.iter().enumerate() {
+        let end = corners[(index + 1) % corners.len()];
+        let direction = IVec2::new((end.x - start.x).signum(), (end.y - start.y).signum());
+        let length = (end.x - start.x).abs() + (end.y - start.y).abs();
+        for step in 0..length {
+            let from = *start + direction * step;
+            segments.push((from, from + direction, segments.len() as i32));
+        }
+    }
+    segments
+}
+///A selection's outline, dashes that alternate between white and black and move along the outline
+///
+///The outline's mesh is only built when the outline changes, the dashes move and keep their width on the screen through it's [MarchingAntsMaterial](MarchingAntsMaterial)
+#[derive(Debug, Default)]
+pub struct MarchingAnts {
+    entity: Option<Entity>,
+    ///The material is kept when the outline is built again
+    material: Option<Handle<MarchingAntsMaterial>>,
+}
+impl MarchingAnts {
+    ///Replaces the outline with a new one, nothing is shown for an empty outline
+    pub fn set_outline(
+        &mut self,
+        commands: &mut Commands,
+        (meshes, materials): (&mut Assets<Mesh>, &mut Assets<MarchingAntsMaterial>),
+        segments: &[OutlineSegment],
+        transform: Transform,
+    ) {
+        self.despawn(commands);
+        if segments.is_empty() {
+            return;
+        }
+        let material = self
+            .material
+            .get_or_insert_with(|| {
+                materials.add(MarchingAntsMaterial {
+                    line_width: MARCHING_ANTS_WIDTH,
+                    dash_offset: 0.0,
+                    dash_length: MARCHING_ANTS_DASH_LENGTH as f32,
+                })
+            })
+            .clone();
+        self.entity = Some(
+            commands
+                .spawn_bundle(MeshBundle {
+                    mesh: meshes.add(get_marching_ants_mesh(segments)),
+                    render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                        crate::MARCHING_ANTS_PIPELINE_HANDLE.typed(),
+                    )]),
+                    visible: Visible {
+                        is_visible: true,
+                        is_transparent: true,
+                    },
+                    transform,
+                    ..Default::default()
+                })
+                .insert(material)
+                .id(),
+        );
+    }
+    ///Moves the dashes by the phase and sets the outline's width, only the material changes
+    pub fn update(
+        &self,
+        materials: &mut Assets<MarchingAntsMaterial>,
+        phase: i32,
+        line_width: f32,
+    ) {
+        if let Some(material_handle) = &self.material {
+            let material = MarchingAntsMaterial {
+                line_width,
+                dash_offset: phase as f32,
+                dash_length: MARCHING_ANTS_DASH_LENGTH as f32,
+            };
+            //Only changing the material when needed, so it's uniforms aren't sent to the gpu every frame
+            if materials.get(material_handle) != Some(&material) {
+                if let Some(shown_material) = materials.get_mut(material_handle) {
+                    *shown_material = material;
+                }
+            }
+        }
+    }
+    pub fn despawn(&mut self, commands: &mut Commands) {
+        if let Some(entity) = self.entity.take() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+///Returns the mesh of an outline, a quad on each segment that the shader pushes out by the outline's width
+fn get_marching_ants_mesh(segments: &[OutlineSegment]) -> Mesh {
+    let mut positions = Vec::with_capacity(segments.len() * 4);
+    let mut offsets = Vec::with_capacity(segments.len() * 4);
+    let mut distances = Vec::with_capacity(segments.len() * 4);
+    let mut indices = Vec::with_capacity(segments.len() * 6);
+    for (from, to, distance) in segments {
+        let direction = (*to - *from).as_f32();
+        //Half of the width goes to each side, the ends go out too so the segments meet at the corners
+        let side = Vec2::new(-direction.y, direction.x) * 0.5;
+        let along = direction * 0.5;
+        let first_vertex = positions.len() as u32;
+        for (corner, offset) in [
+            (from, -along - side),
+            (from, -along + side),
+            (to, along + side),
+            (to, along - side),
+        ]
+        .iter()
+        {
+            positions.push([corner.x as f32, corner.y as f32, 0.0]);
+            offsets.push([offset.x, offset.y]);
+            distances.push(*distance as f32);
+        }
+        indices.extend([0, 1, 2, 0, 2, 3].iter().map(|index| first_vertex + index));
+    }
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute("Vertex_Offset", offsets);
+    mesh.set_attribute("Vertex_Distance", distances);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
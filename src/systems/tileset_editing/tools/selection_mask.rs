@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use bevy_common::input::marker_components::MainCamera;
+use bevy_prototype_lyon::prelude::*;
+
+use super::{
+    fill::flood_tiles,
+    picker::is_alt_pressed,
+    rasterizing::get_polygon_pixels,
+    selection::{
+        get_marching_ants_phase, get_marching_ants_width, get_tileset_pixels_transform,
+        MarchingAnts, OutlineSegment,
+    },
+};
+use crate::{
+    data::{
+        resources::{MousePixelPosition, SelectedTool},
+        selection::{MarchingAntsMaterial, MaskOperation, MaskSettings, SelectionMask},
+        shared_components::CurrentlySelected,
+        tile_entity::{TileData, TileSettings},
+        tileset_entity::{TileSetSettings, TileSetTiles},
+    },
+    systems::file_handling::exporting::get_tileset_image_size,
+};
+///Returns how a new selection is combined with the mask, Shift adds, Alt subtracts and both intersect
+pub fn get_mask_operation(
+    keyboard_input: &Input<KeyCode>,
+    mask_settings: &MaskSettings,
+) -> MaskOperation {
+    let shift = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+    match (shift, is_alt_pressed(keyboard_input)) {
+        (true, true) => MaskOperation::Intersect,
+        (true, false) => MaskOperation::Add,
+        (false, true) => MaskOperation::Subtract,
+        (false, false) => mask_settings.operation,
+    }
+}
+///The freehand shape being dragged by the lasso, kept between frames
+#[derive(Debug, Default)]
+pub struct LassoDragState {
+    ///The pixels the mouse went through, they're the shape's vertices
+    vertices: Vec<IVec2>,
+    ///Decided by the modifier keys held when the drag started
+    operation: Option<MaskOperation>,
+    ///The line that shows the user the shape
+    preview: Option<Entity>,
+    ///How many vertices the preview was built from, so it's only rebuilt when the shape grows
+    previewed_vertices: usize,
+}
+///Drags a freehand shape, the pixels inside it are combined with the selected tileset's [SelectionMask](SelectionMask) when the mouse is released
+pub fn lasso_tool(
+    mut commands: Commands,
+    mut drag_state: Local<LassoDragState>,
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    tool: Res<SelectedTool>,
+    mask_settings: Res<MaskSettings>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut tileset_query: Query<
+        (&TileSetSettings, &GlobalTransform, &mut SelectionMask),
+        With<CurrentlySelected>,
+    >,
+) {
+    let is_lasso = matches!(*tool, SelectedTool::Lasso);
+    if !is_lasso || drag_state.operation.is_none() {
+        //Switching tools in the middle of a drag cancels it
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        *drag_state = LassoDragState::default();
+        //The drag can only start on the tileset
+        if !is_lasso
+            || !mouse_input.just_pressed(MouseButton::Left)
+            || mouse_pixel_position.pixel_position.is_none()
+        {
+            return;
+        }
+        drag_state.operation = Some(get_mask_operation(&keyboard_input, &mask_settings));
+    }
+    let (tileset_settings, global_transform, mut selection_mask) = match tileset_query.single_mut()
+    {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    if mouse_input.pressed(MouseButton::Left) {
+        //The shape doesn't grow while the mouse is outside the tileset
+        if let Some(mouse_pixel) = mouse_pixel_position.pixel_position {
+            let mouse_pixel = mouse_pixel.as_i32();
+            if drag_state.vertices.last() != Some(&mouse_pixel) {
+                drag_state.vertices.push(mouse_pixel);
+            }
+        }
+        if drag_state.vertices.len() == drag_state.previewed_vertices {
+            return;
+        }
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        //The line goes through the middle of the pixels
+        let points = drag_state
+            .vertices
+            .iter()
+            .map(|vertex| vertex.as_f32() + Vec2::splat(0.5))
+            .collect();
+        let line_width = camera_query.single().map_or(1.0, |camera_transform| {
+            get_marching_ants_width(camera_transform, global_transform)
+        });
+        drag_state.preview = Some(
+            commands
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::Polygon {
+                        points,
+                        closed: false,
+                    },
+                    ShapeColors::new(Color::WHITE),
+                    DrawMode::Stroke(StrokeOptions::default().with_line_width(line_width)),
+                    get_tileset_pixels_transform(tileset_settings, global_transform, 11.0),
+                ))
+                .id(),
+        );
+        drag_state.previewed_vertices = drag_state.vertices.len();
+    } else {
+        //The mouse was released, the shape is closed and the pixels inside it are selected
+        let (image_width, image_height) = get_tileset_image_size(tileset_settings);
+        let mut selected = vec![false; (image_width * image_height) as usize];
+        for pixel in get_polygon_pixels(&drag_state.vertices) {
+            if pixel.x >= 0
+                && pixel.y >= 0
+                && pixel.x < image_width as i32
+                && pixel.y < image_height as i32
+            {
+                selected[((image_height as i32 - 1 - pixel.y) * image_width as i32 + pixel.x)
+                    as usize] = true;
+            }
+        }
+        if let Some(operation) = drag_state.operation {
+            selection_mask.combine(selected, UVec2::new(image_width, image_height), operation);
+        }
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        *drag_state = LassoDragState::default();
+    }
+}
+///Combines the pixels with the clicked pixel's color with the selected tileset's [SelectionMask](SelectionMask)
+pub fn magic_wand_tool(
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    tool: Res<SelectedTool>,
+    mask_settings: Res<MaskSettings>,
+    mut tileset_query: Query<
+        (&TileSetSettings, &TileSetTiles, &mut SelectionMask),
+        With<CurrentlySelected>,
+    >,
+    tile_query: Query<&TileData>,
+) {
+    if !matches!(*tool, SelectedTool::MagicWand) || !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if let (Some(mouse_pixel), Ok((tileset_settings, tileset_tiles, mut selection_mask))) = (
+        mouse_pixel_position.pixel_position,
+        tileset_query.single_mut(),
+    ) {
+        //Selecting goes through the tiles like filling
+        let tile_masks = flood_tiles(
+            (tileset_settings, tileset_tiles),
+            mouse_pixel.as_i32(),
+            mask_settings.wand_contiguous,
+            true,
+            mask_settings.wand_tolerance,
+            |tile_entity| tile_query.get(tile_entity).ok(),
+        );
+        let tile_settings = TileSettings {
+            tile_width: tileset_settings.tile_width,
+            tile_height: tileset_settings.tile_height,
+        };
+        let tile_size = IVec2::new(
+            tileset_settings.tile_width as i32,
+            tileset_settings.tile_height as i32,
+        );
+        let (image_width, image_height) = get_tileset_image_size(tileset_settings);
+        let mut selected = vec![false; (image_width * image_height) as usize];
+        for tile_mask in tile_masks.iter() {
+            for pixel in tile_mask.filled_pixels(&tile_settings) {
+                let pixel = tile_mask.tile_position * tile_size + pixel.as_i32();
+                //The mask's rows start at the top of the tileset
+                selected[((image_height as i32 - 1 - pixel.y) * image_width as i32 + pixel.x)
+                    as usize] = true;
+            }
+        }
+        selection_mask.combine(
+            selected,
+            UVec2::new(image_width, image_height),
+            get_mask_operation(&keyboard_input, &mask_settings),
+        );
+    }
+}
+///The entities that show the selection mask, kept between frames
+#[derive(Debug, Default)]
+pub struct SelectionMaskVisuals {
+    ants: MarchingAnts,
+    ///Whether the ants show the selected tileset's mask, they're built again when it changes
+    is_shown: bool,
+}
+///Shows the outline of the selected tileset's [SelectionMask](SelectionMask) as marching ants
+pub fn show_selection_mask(
+    mut commands: Commands,
+    mut visuals: Local<SelectionMaskVisuals>,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ants_materials: ResMut<Assets<MarchingAntsMaterial>>,
+    tileset_query: Query<
+        (
+            &TileSetSettings,
+            &GlobalTransform,
+            &SelectionMask,
+            ChangeTrackers<SelectionMask>,
+            ChangeTrackers<CurrentlySelected>,
+        ),
+        With<CurrentlySelected>,
+    >,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let (tileset, camera_transform) = match (tileset_query.single(), camera_query.single()) {
+        (Ok(tileset), Ok(camera_transform)) => (tileset, camera_transform),
+        _ => {
+            visuals.ants.despawn(&mut commands);
+            visuals.is_shown = false;
+            return;
+        }
+    };
+    let (tileset_settings, global_transform, selection_mask, mask_tracker, selected_tracker) =
+        tileset;
+    //Every tileset has it's own mask, the outline's mesh is only built when the mask changes
+    if !visuals.is_shown || mask_tracker.is_changed() || selected_tracker.is_added() {
+        visuals.ants.set_outline(
+            &mut commands,
+            (&mut meshes, &mut ants_materials),
+            &get_mask_outline(selection_mask),
+            get_tileset_pixels_transform(tileset_settings, global_transform, 11.0),
+        );
+        visuals.is_shown = true;
+    }
+    //The dashes move in the shader
+    visuals.ants.update(
+        &mut ants_materials,
+        get_marching_ants_phase(&time),
+        get_marching_ants_width(camera_transform, global_transform),
+    );
+}
+///Returns the edges between the mask's selected pixels and the pixels around them
+fn get_mask_outline(selection_mask: &SelectionMask) -> Vec<OutlineSegment> {
+    let mut segments = Vec::new();
+    if selection_mask.mask.is_none() {
+        return segments;
+    }
+    let size = selection_mask.size.as_i32();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let pixel = IVec2::new(x, y);
+            if !selection_mask.can_paint(pixel) {
+                continue;
+            }
+            //The dashes are diagonal stripes, so they march along edges going any way
+            let distance = x + y;
+            if !selection_mask.can_paint(pixel - IVec2::X) {
+                segments.push((pixel, pixel + IVec2::Y, distance));
+            }
+            if !selection_mask.can_paint(pixel + IVec2::X) {
+                segments.push((pixel + IVec2::X, pixel + IVec2::ONE, distance));
+            }
+            if !selection_mask.can_paint(pixel - IVec2::Y) {
+                segments.push((pixel, pixel + IVec2::X, distance));
+            }
+            if !selection_mask.can_paint(pixel + IVec2::Y) {
+                segments.push((pixel + IVec2::Y, pixel + IVec2::ONE, distance));
+            }
+        }
+    }
+    segments
+}
@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use super::{
+    can_draw_on_active_layer, get_just_pressed_drawing_button,
+    rasterizing::{
+        constrain_line_end, constrain_to_square, get_ellipse_pixels, get_line_pixels,
+        get_rectangle_pixels,
+    },
+};
+use crate::{
+    data::{
+        history::CurrentStroke,
+        layers::TileSetLayers,
+        resources::{DrawingColors, MousePixelPosition, SelectedTool, ShapeSettings},
+        shared_components::CurrentlySelected,
+        tile_entity::{TileData, TilePosition, TileSettings, TileTexture},
+        tileset_entity::{TileSetSettings, TileSetTiles},
+    },
+    systems::initializing::get_total_tileset_size_pixels,
+};
+///The shape being dragged by the shape tools, kept between frames
+#[derive(Debug, Default)]
+pub struct ShapeDragState {
+    ///The pixel where the mouse was pressed
+    start_pixel: Option<IVec2>,
+    ///The mouse button that started the drag, it decides the shape's color
+    button: Option<MouseButton>,
+    ///The last pixel the mouse was on, the shape is drawn up to it
+    end_pixel: Option<IVec2>,
+    ///The shape that shows the user what will be drawn
+    preview: Option<Entity>,
+    ///What the preview was built from, so it's only rebuilt when something changes
+    previewed: Option<(IVec2, IVec2, bool, [u8; 4])>,
+}
+///Shows a preview of the line, rectangle or ellipse while the mouse is dragged, and draws it when the mouse is released
+pub fn shape_tool(
+    mut commands: Commands,
+    mut drag_state: Local<ShapeDragState>,
+    mouse_pixel_position: Res<MousePixelPosition>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    tool: Res<SelectedTool>,
+    shape_settings: Res<ShapeSettings>,
+    drawing_colors: Res<DrawingColors>,
+    mut current_stroke: ResMut<CurrentStroke>,
+    tileset_query: Query<
+        (&TileSetSettings, &TileSetTiles, &GlobalTransform),
+        With<CurrentlySelected>,
+    >,
+    layers_query: Query<&TileSetLayers, With<CurrentlySelected>>,
+    mut tile_query: Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    let is_shape_tool = matches!(
+        *tool,
+        SelectedTool::Line | SelectedTool::Rectangle | SelectedTool::Ellipse
+    );
+    if !is_shape_tool || drag_state.start_pixel.is_none() {
+        //Switching tools in the middle of a drag cancels it
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        *drag_state = ShapeDragState::default();
+        match get_just_pressed_drawing_button(&mouse_input) {
+            Some(button) if is_shape_tool && can_draw_on_active_layer(&layers_query) => {
+                drag_state.button = Some(button)
+            }
+            _ => return,
+        }
+        //The drag can only start on the tileset
+        match mouse_pixel_position.pixel_position {
+            Some(mouse_pixel) => drag_state.start_pixel = Some(mouse_pixel.as_i32()),
+            None => return,
+        }
+    }
+    //The shape keeps it's last end while the mouse is outside the tileset
+    if let Some(mouse_pixel) = mouse_pixel_position.pixel_position {
+        drag_state.end_pixel = Some(mouse_pixel.as_i32());
+    }
+    let (start_pixel, end_pixel) = match (drag_state.start_pixel, drag_state.end_pixel) {
+        (Some(start_pixel), Some(end_pixel)) => (start_pixel, end_pixel),
+        _ => return,
+    };
+    //Shift makes squares, circles and lines that are straight or diagonal
+    let end_pixel =
+        if keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift) {
+            match *tool {
+                SelectedTool::Line => constrain_line_end(start_pixel, end_pixel),
+                _ => constrain_to_square(start_pixel, end_pixel),
+            }
+        } else {
+            end_pixel
+        };
+    let button = drag_state.button.unwrap_or(MouseButton::Left);
+    let color = drawing_colors.get(button);
+    let filled = shape_settings.filled && !matches!(*tool, SelectedTool::Line);
+    if mouse_input.pressed(button) {
+        let previewed = Some((start_pixel, end_pixel, filled, color));
+        if drag_state.previewed == previewed {
+            return;
+        }
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        if let Ok((tileset_settings, _, global_transform)) = tileset_query.single() {
+            drag_state.preview = Some(spawn_shape_preview(
+                &mut commands,
+                &tool,
+                (start_pixel, end_pixel),
+                filled,
+                color,
+                tileset_settings,
+                global_transform,
+            ));
+        }
+        drag_state.previewed = previewed;
+    } else {
+        //The mouse was released, the shape is drawn into the tiles
+        let pixels = match *tool {
+            SelectedTool::Line => get_line_pixels(start_pixel, end_pixel),
+            SelectedTool::Rectangle => get_rectangle_pixels(start_pixel, end_pixel, filled),
+            _ => get_ellipse_pixels(start_pixel, end_pixel, filled),
+        };
+        if let Ok((tileset_settings, tileset_tiles, _)) = tileset_query.single() {
+            draw_pixels_in_tileset(
+                &pixels,
+                color,
+                (tileset_settings, tileset_tiles),
+                &mut current_stroke,
+                &mut tile_query,
+            );
+        }
+        if let Some(preview) = drag_state.preview.take() {
+            commands.entity(preview).despawn();
+        }
+        *drag_state = ShapeDragState::default();
+    }
+}
+///Spawns a vector shape above the tileset that looks like the shape that will be drawn
+fn spawn_shape_preview(
+    commands: &mut Commands,
+    tool: &SelectedTool,
+    (start_pixel, end_pixel): (IVec2, IVec2),
+    filled: bool,
+    color: [u8; 4],
+    tileset_settings: &TileSetSettings,
+    global_transform: &GlobalTransform,
+) -> Entity {
+    //The shape is built in tileset pixels, the transform puts it on top of the tileset like the tiles
+    let tileset_size = get_total_tileset_size_pixels(tileset_settings);
+    let transform = Transform {
+        translation: (-tileset_size / 2.0 * global_transform.scale.truncate()).extend(10.0),
+        scale: global_transform.scale,
+        ..Default::default()
+    };
+    let min = start_pixel.min(end_pixel).as_f32();
+    let max = start_pixel.max(end_pixel).as_f32() + Vec2::ONE;
+    let colors = ShapeColors::new(Color::rgba_u8(color[0], color[1], color[2], color[3]));
+    //Outlines go through the middle of the edge pixels
+    let outline_mode = DrawMode::Stroke(StrokeOptions::default().with_line_width(1.0));
+    let fill_mode = DrawMode::Fill(FillOptions::default());
+    let preview_bundle = match tool {
+        SelectedTool::Line => GeometryBuilder::build_as(
+            &shapes::Line(
+                start_pixel.as_f32() + Vec2::splat(0.5),
+                end_pixel.as_f32() + Vec2::splat(0.5),
+            ),
+            colors,
+            outline_mode,
+            transform,
+        ),
+        SelectedTool::Rectangle => {
+            let (min, max) = if filled {
+                (min, max)
+            } else {
+                (min + Vec2::splat(0.5), max - Vec2::splat(0.5))
+            };
+            GeometryBuilder::build_as(
+                &shapes::Polygon {
+                    points: vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)],
+                    closed: true,
+                },
+                colors,
+                if filled { fill_mode } else { outline_mode },
+                transform,
+            )
+        }
+        _ => {
+            let radii = (max - min) / 2.0;
+            GeometryBuilder::build_as(
+                &shapes::Ellipse {
+                    radii: if filled {
+                        radii
+                    } else {
+                        radii - Vec2::splat(0.5)
+                    },
+                    center: (min + max) / 2.0,
+                },
+                colors,
+                if filled { fill_mode } else { outline_mode },
+                transform,
+            )
+        }
+    };
+    commands.spawn_bundle(preview_bundle).id()
+}
+///Sets tileset pixels to a color in the tiles they're in, pixels outside of the tileset are skipped
+pub fn draw_pixels_in_tileset(
+    pixels: &[IVec2],
+    color: [u8; 4],
+    (tileset_settings, tileset_tiles): (&TileSetSettings, &TileSetTiles),
+    current_stroke: &mut CurrentStroke,
+    query: &mut Query<(
+        Entity,
+        &TileSettings,
+        &TilePosition,
+        &mut TileData,
+        &mut TileTexture,
+    )>,
+) {
+    for pixel in pixels {
+        //Only the tile the pixel is in is looked at
+        let tile_entity = match tileset_tiles.get_at_pixel(tileset_settings, *pixel) {
+            Some(tile_entity) => tile_entity,
+            None => continue,
+        };
+        if let Ok((entity, tile_settings, tile_position, mut tile_data, mut tile_texture)) =
+            query.get_mut(tile_entity)
+        {
+            let pixel_in_tile = *pixel - tile_position.min_pixel(tile_settings).as_i32();
+            current_stroke.record(entity, &tile_data);
+            tile_data.set_pixel(tile_settings, pixel_in_tile.as_u32(), color);
+            tile_texture.mark_dirty(tile_settings, pixel_in_tile.as_u32());
+        }
+    }
+}
@@ -1,14 +1,18 @@
 use crate::data::{
+    chunks::{TileSetChunk, TileSetChunkMaterial, TileSetChunks},
+    grid::{GridSettings, TileSetGrid, TileSetGridMaterial},
+    layers::{TileLayers, TileSetLayers},
+    resources::{get_transparent_texture, BlankChunkMaterials, TextureUpload, TextureUploads},
     shared_components::CurrentlySelected,
-    tile_entity::{TileData, TileRect, TileSettings},
-    tileset_entity::{NewlySelected, TileSetView},
-};
-use bevy::{
-    prelude::*,
-    render::texture::{Extent3d, TextureDimension, TextureFormat},
+    tile_entity::{TileData, TilePosition, TileRect, TileSettings, TileTexture},
+    tileset_entity::{NewlySelected, TileSetSettings, TileSetView},
 };
+use bevy::prelude::*;
 use bevy_common::input::{data_components::CameraZoomLimit, marker_components::MainCamera};
 ///Changes the view from one tileset to another
+///
+///Only the tileset's [TileSetChunk](TileSetChunk)s and it's [TileSetGrid](TileSetGrid) are shown or hidden, the tiles themselves aren't rendered.
+///Only the tileset entity is marked [CurrentlySelected](CurrentlySelected), the tools get to it's tiles through it's [TileSetTiles](crate::data::tileset_entity::TileSetTiles)
 pub fn update_selected_tileset(
     mut commands: Commands,
     mut camera_transform_query: Query<(&mut Transform, &mut CameraZoomLimit), With<MainCamera>>,
@@ -16,15 +20,8 @@ pub fn update_selected_tileset(
         (Entity, &TileSetView),
         (With<NewlySelected>, Without<CurrentlySelected>),
     >,
-    mut currently_unselected_children_query: Query<
-        (Entity, &Parent, &mut Visible),
-        (With<TileSettings>, Without<CurrentlySelected>),
-    >,
     mut currently_selected_query: Query<(Entity, &mut TileSetView), With<CurrentlySelected>>,
-    mut currently_selected_children_query: Query<
-        (Entity, &mut Visible),
-        (With<TileSettings>, With<CurrentlySelected>),
-    >,
+    mut chunk_query: Query<(&Parent, &mut Visible), Or<(With<TileSetChunk>, With<TileSetGrid>)>>,
 ) {
     //If there is some newly selected tileset
     if let Ok((newly_selected_tileset_ent, newly_selected_tileset_view)) =
@@ -34,12 +31,11 @@ pub fn update_selected_tileset(
         if let Ok((currently_selected_tileset_ent, mut currently_selected_tileset_view)) =
             currently_selected_query.single_mut()
         {
-            //For each child of this parent
-            for (entity, mut visible) in currently_selected_children_query.iter_mut() {
-                //Make these children transparent
-                visible.is_visible = false;
-                //Remove their currently selected marker so our queries will run on less Tiles
-                commands.entity(entity).remove::<CurrentlySelected>();
+            //Hide it's chunks and grid
+            for (parent, mut visible) in chunk_query.iter_mut() {
+                if parent.0 == currently_selected_tileset_ent {
+                    visible.is_visible = false;
+                }
             }
             //Remove the currently selected flag from the tileset entity because it's no longer selected
             commands
@@ -61,47 +57,147 @@ pub fn update_selected_tileset(
             .remove::<NewlySelected>()
             //Then add it a currently selected flag
             .insert(CurrentlySelected);
-        //Filter the query on children that their parent is the one newly selected
-        let filtered = currently_unselected_children_query
-            .iter_mut()
-            .filter(|(_entity, parent, _visible)| parent.0 == newly_selected_tileset_ent);
-        for (entity, _parent, mut visible) in filtered.into_iter() {
-            //Mark the child selected
-            commands.entity(entity).insert(CurrentlySelected);
-            //Make the child visible
-            visible.is_visible = true;
+        //Show the newly selected tileset's chunks and grid
+        for (parent, mut visible) in chunk_query.iter_mut() {
+            if parent.0 == newly_selected_tileset_ent {
+                visible.is_visible = true;
+            }
         }
     }
 }
 
-///This function updates the [Texture](Texture) of [ColorMaterial](ColorMaterial)s used by [TileBundle](TileBundle)s when their [TileData](TileData) is changed
+///This function updates the textures of the [TileSetChunk](TileSetChunk)s that show [TileBundle](crate::data::tile_entity::TileBundle)s when their [TileData](TileData) is changed
+///
+///A tile shows all of it's visible layers blended together, so changing the [TileSetLayers](TileSetLayers) updates every tile.
+///Each chunk keeps the same texture, only the rectangle of a tile's pixels marked in it's [TileTexture](TileTexture) is sent to the [TextureUploadNode](crate::systems::texture_uploading::TextureUploadNode).
+///Blank chunks share a material from the [BlankChunkMaterials](BlankChunkMaterials), a chunk gets it's own when something is drawn in one of it's tiles
 pub fn update_textures_for_changed_tile_data(
     mut textures: ResMut<Assets<Texture>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    query: Query<
-        (&TileSettings, &TileData, &Handle<ColorMaterial>),
+    mut materials: ResMut<Assets<TileSetChunkMaterial>>,
+    mut texture_uploads: ResMut<TextureUploads>,
+    mut blank_chunk_materials: ResMut<BlankChunkMaterials>,
+    tileset_query: Query<
         (
-            With<CurrentlySelected>, //This should be redundant since Changed<TileData> should be only for currently selected tiles but whatever, i don't think this hurts
-            Changed<TileData>,
+            &TileSetSettings,
+            &TileSetLayers,
+            &TileSetChunks,
+            &Children,
+            ChangeTrackers<TileSetLayers>,
+            ChangeTrackers<CurrentlySelected>,
         ),
+        With<CurrentlySelected>,
     >,
+    mut tile_query: Query<(
+        &TileSettings,
+        &TilePosition,
+        &TileData,
+        &TileLayers,
+        &mut TileTexture,
+        ChangeTrackers<TileData>,
+        ChangeTrackers<TileLayers>,
+    )>,
+    mut chunk_query: Query<(&TileSetChunk, &mut Handle<TileSetChunkMaterial>)>,
+) {
+    if let Ok((
+        tileset_settings,
+        tileset_layers,
+        tileset_chunks,
+        children,
+        tileset_layers_tracker,
+        selected_tracker,
+    )) = tileset_query.single()
+    {
+        //Tiles of tilesets that weren't selected could have been changed without updating their textures
+        let update_every_tile = tileset_layers_tracker.is_changed() || selected_tracker.is_added();
+        let tile_size = UVec2::new(
+            tileset_settings.tile_width as u32,
+            tileset_settings.tile_height as u32,
+        );
+        for child in children.iter() {
+            if let Ok((
+                tile_settings,
+                tile_position,
+                tile_data,
+                tile_layers,
+                mut tile_texture,
+                tile_data_tracker,
+                tile_layers_tracker,
+            )) = tile_query.get_mut(*child)
+            {
+                let update_whole_tile = update_every_tile || tile_layers_tracker.is_changed();
+                if !update_whole_tile && !tile_data_tracker.is_changed() {
+                    continue;
+                }
+                let pixels = tile_layers.composite(tileset_layers, tile_data);
+                //Blank tiles that show nothing yet have nothing to upload
+                if pixels.is_empty() && !tile_texture.is_shown {
+                    tile_texture.dirty_rect = None;
+                    continue;
+                }
+                let (chunk, mut material_handle) = match tileset_chunks
+                    .get_chunk(tile_position.position)
+                    .and_then(|chunk_entity| chunk_query.get_mut(chunk_entity).ok())
+                {
+                    Some(chunk) => chunk,
+                    None => continue,
+                };
+                //The first pixels drawn in a blank chunk get it's own texture and material, it starts transparent like the blank one
+                let chunk_size = chunk.size_in_tiles * tile_size;
+                let blank_material_handle =
+                    blank_chunk_materials.get_or_add(chunk_size, &mut textures, &mut materials);
+                if *material_handle == blank_material_handle {
+                    *material_handle = materials.add(TileSetChunkMaterial {
+                        texture: textures.add(get_transparent_texture(chunk_size)),
+                    });
+                }
+                //This shouldn't fail really, i shouldn't delete any of them anywhere
+                let texture_handle = match materials.get(&*material_handle) {
+                    Some(material) => material.texture.clone(),
+                    None => continue,
+                };
+                //Changing the layers changes every pixel the tile shows, not just the ones the tools marked
+                if update_whole_tile {
+                    tile_texture.dirty_rect = None;
+                }
+                let (min, max) = tile_texture.take_dirty_rect(tile_settings);
+                //Blank pixels are transparent in the texture
+                let mut rect_pixels =
+                    Vec::with_capacity(((max.x - min.x) * (max.y - min.y) * 4) as usize);
+                for y in min.y..max.y {
+                    let row_start = (y as usize * tile_settings.tile_width + min.x as usize) * 4;
+                    let row_end = (y as usize * tile_settings.tile_width + max.x as usize) * 4;
+                    if pixels.is_empty() {
+                        rect_pixels.resize(rect_pixels.len() + row_end - row_start, 0);
+                    } else {
+                        rect_pixels.extend_from_slice(&pixels[row_start..row_end]);
+                    }
+                }
+                texture_uploads.uploads.push(TextureUpload {
+                    texture_handle,
+                    origin: chunk.get_texture_origin(tileset_settings, tile_position.position, min),
+                    size: max - min,
+                    pixels: rect_pixels,
+                });
+                tile_texture.is_shown = !pixels.is_empty();
+            }
+        }
+    }
+}
+///Updates the [TileSetGridMaterial](TileSetGridMaterial)s of every tileset when the [GridSettings](GridSettings) change
+pub fn update_grid_materials(
+    grid_settings: Res<GridSettings>,
+    mut materials: ResMut<Assets<TileSetGridMaterial>>,
+    grid_query: Query<&Handle<TileSetGridMaterial>, With<TileSetGrid>>,
 ) {
-    for (tile_settings, tile_data, material_handle) in query.iter() {
-        //This shouldn't fail really, i shouldn't delete any of them anywhere
-        let material = materials.get_mut(material_handle).unwrap();
-        //Add this texture to the resource and get the handle back
-        let texture_handle = textures.add(Texture::new(
-            Extent3d::new(
-                tile_settings.tile_width as u32,
-                tile_settings.tile_height as u32,
-                1,
-            ),
-            TextureDimension::D2,
-            tile_data.data.clone(),
-            TextureFormat::Rgba8UnormSrgb,
-        ));
-        //Set the material's texture handle
-        material.texture = Some(texture_handle);
+    if !grid_settings.is_changed() {
+        return;
+    }
+    for material_handle in grid_query.iter() {
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.tile_grid_color = grid_settings.get_tile_grid_color();
+            material.pixel_grid_color = grid_settings.get_pixel_grid_color();
+            material.pixel_grid_min_zoom = grid_settings.pixel_grid_min_zoom;
+        }
     }
 }
 ///This function recalculates a tiles rect in world coordinates
@@ -0,0 +1,30 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 2, binding = 0) uniform texture2D TileSetChunkMaterial_texture;
+layout(set = 2, binding = 1) uniform sampler TileSetChunkMaterial_texture_sampler;
+
+void main() {
+    //Get the color of the current fragment from the texture
+    vec4 color = texture(
+        sampler2D(TileSetChunkMaterial_texture, TileSetChunkMaterial_texture_sampler),
+        v_Uv);
+    //If the color is transparent
+    if(color.a == 0.0){
+        //Get the fragment's pixel position in the chunk's texture
+        vec2 pixel = v_Uv * textureSize(sampler2D(TileSetChunkMaterial_texture, TileSetChunkMaterial_texture_sampler), 0);
+        //If the pixel's position fits in the equation y=x+b where b%2==0  (a straight line of pixels from every even row, creates a checkerboard pattern)
+        if(mod(int(pixel.y)-int(pixel.x),2.0)==0.0){
+            //Color the fragment slightly white
+            color = vec4(0.502,0.502,0.502,1.0);
+        }
+        else{
+            //Color the fragment even whiter
+            color = vec4(0.802,0.802,0.802,1.0);
+        }
+    }
+    o_Target = color;
+}
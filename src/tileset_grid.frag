@@ -0,0 +1,42 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 2, binding = 0) uniform TileSetGridMaterial_tile_size {
+    vec2 TileSize;
+};
+layout(set = 2, binding = 1) uniform TileSetGridMaterial_tileset_size {
+    vec2 TileSetSize;
+};
+layout(set = 2, binding = 2) uniform TileSetGridMaterial_tile_grid_color {
+    vec4 TileGridColor;
+};
+layout(set = 2, binding = 3) uniform TileSetGridMaterial_pixel_grid_color {
+    vec4 PixelGridColor;
+};
+layout(set = 2, binding = 4) uniform TileSetGridMaterial_pixel_grid_min_zoom {
+    float PixelGridMinZoom;
+};
+
+void main() {
+    //Get the fragment's pixel position in the tileset
+    vec2 pixel = v_Uv * TileSetSize;
+    //How many of the tileset's pixels a pixel on the screen covers, it changes with the zoom
+    vec2 pixels_per_fragment = fwidth(pixel);
+    //How far the fragment is from the closest border between tiles and between pixels, in pixels on the screen
+    vec2 tile_border_distance = abs(mod(pixel + TileSize * 0.5, TileSize) - TileSize * 0.5) / pixels_per_fragment;
+    vec2 pixel_border_distance = abs(fract(pixel + 0.5) - 0.5) / pixels_per_fragment;
+    //The lines keep their width on the screen however far the camera is zoomed
+    vec4 color = vec4(0.0,0.0,0.0,0.0);
+    if(min(tile_border_distance.x, tile_border_distance.y) < 1.0){
+        color = TileGridColor;
+    }
+    //The pixel grid would cover everything when zoomed out, so it only shows when the pixels are big enough
+    else if(1.0 / max(pixels_per_fragment.x, pixels_per_fragment.y) >= PixelGridMinZoom
+        && min(pixel_border_distance.x, pixel_border_distance.y) < 0.5){
+        color = PixelGridColor;
+    }
+    o_Target = color;
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::data::{
+    animations::{AnimationPlayback, TileSetAnimations},
+    chunks::{TileSetChunk, TileSetChunkMaterial, TileSetChunks},
+    shared_components::CurrentlySelected,
+    tileset_entity::TileSetSettings,
+};
+///The id egui knows the texture of the previewed tile's [TileSetChunk](TileSetChunk) by
+pub const ANIMATION_PREVIEW_TEXTURE_ID: u64 = 0;
+///How much the preview is zoomed in, kept between frames
+#[derive(Debug)]
+pub struct AnimationPreviewWindowData {
+    zoom: f32,
+}
+impl Default for AnimationPreviewWindowData {
+    fn default() -> Self {
+        Self { zoom: 4.0 }
+    }
+}
+///Drawing the window that shows the current frame of the animation selected in the timeline
+pub fn draw_animation_preview_window(
+    mut egui_context: ResMut<EguiContext>,
+    playback: Res<AnimationPlayback>,
+    mut preview_window_data: Local<AnimationPreviewWindowData>,
+    tileset_query: Query<
+        (&TileSetAnimations, &TileSetSettings, &TileSetChunks),
+        With<CurrentlySelected>,
+    >,
+    chunk_query: Query<(&TileSetChunk, &Handle<TileSetChunkMaterial>)>,
+    materials: Res<Assets<TileSetChunkMaterial>>,
+) {
+    let (tileset_animations, tileset_settings, tileset_chunks) = match tileset_query.single() {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    let frame = match playback
+        .animation
+        .and_then(|animation_index| tileset_animations.animations.get(animation_index))
+        .and_then(|animation| animation.frames.get(playback.frame))
+    {
+        Some(frame) => frame,
+        None => return,
+    };
+    //The previewed frame changes while playing, so the preview looks up it's tile's chunk every frame
+    let (chunk, material_handle) = match tileset_chunks
+        .get_chunk(frame.tile_position)
+        .and_then(|chunk_entity| chunk_query.get(chunk_entity).ok())
+    {
+        Some(chunk) => chunk,
+        None => return,
+    };
+    if let Some(material) = materials.get(material_handle) {
+        egui_context.set_egui_texture(ANIMATION_PREVIEW_TEXTURE_ID, material.texture.clone());
+    }
+    //Only the tile's part of the chunk's texture is shown
+    let chunk_size = chunk.size_in_tiles.as_f32()
+        * Vec2::new(
+            tileset_settings.tile_width as f32,
+            tileset_settings.tile_height as f32,
+        );
+    let uv_min = chunk
+        .get_texture_origin(tileset_settings, frame.tile_position, UVec2::ZERO)
+        .as_f32()
+        / chunk_size;
+    let uv_max = uv_min
+        + Vec2::new(
+            tileset_settings.tile_width as f32,
+            tileset_settings.tile_height as f32,
+        ) / chunk_size;
+    let ctx = egui_context.ctx();
+    egui::Window::new("Preview").show(ctx, |ui| {
+        ui.add(egui::Slider::new(&mut preview_window_data.zoom, 1.0..=16.0).text("Zoom"));
+        ui.add(
+            egui::Image::new(
+                egui::TextureId::User(ANIMATION_PREVIEW_TEXTURE_ID),
+                egui::vec2(
+                    tileset_settings.tile_width as f32 * preview_window_data.zoom,
+                    tileset_settings.tile_height as f32 * preview_window_data.zoom,
+                ),
+            )
+            .uv(egui::Rect::from_min_max(
+                egui::pos2(uv_min.x, uv_min.y),
+                egui::pos2(uv_max.x, uv_max.y),
+            )),
+        );
+    });
+}
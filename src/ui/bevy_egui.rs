@@ -1,10 +1,21 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_egui::{egui, EguiContext};
 
+use std::path::PathBuf;
+
+use super::color_widgets::rgba_ui;
 use crate::data::{
+    animations::DEFAULT_FRAME_DURATION_MS,
+    events::{
+        ExportAnimationEvent, ExportTileSetEvent, OpenImageEvent, OpenProjectEvent,
+        SaveProjectEvent,
+    },
+    grid::GridSettings,
+    resources::{ProjectPath, TileSetOrderCounter},
     shared_components::CurrentlySelected,
-    tileset_entity::{NewlySelected, TileSetBundle, TileSetName, TileSetSettings},
+    tileset_entity::{NewlySelected, TileSetBundle, TileSetName, TileSetOrder, TileSetSettings},
 };
+use crate::systems::file_handling::exporting::parse_tile_positions;
 ///The Current Selection of the Context Menu
 #[derive(PartialEq, Eq, Debug)]
 pub enum ContextMenuState {
@@ -18,10 +29,48 @@ impl Default for ContextMenuState {
     }
 }
 ///The Current Selection of the File Context Menu
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum SelectedFileContextMenuItem {
     None,
     New,
+    Open,
+    OpenImage,
+    SaveAs,
+    Export,
+    ExportAnimation,
+}
+///The text the user typed in the file windows, kept between frames
+#[derive(Debug)]
+pub struct FileWindowsData {
+    project_path: String,
+    export_path: String,
+    open_image_path: String,
+    open_image_settings: TileSetSettings,
+    export_animation_path: String,
+    export_animation_tiles: String,
+    export_animation_frame_delay_ms: u32,
+}
+impl Default for FileWindowsData {
+    fn default() -> Self {
+        Self {
+            project_path: String::default(),
+            export_path: String::default(),
+            open_image_path: String::default(),
+            open_image_settings: TileSetSettings::default(),
+            export_animation_path: String::default(),
+            export_animation_tiles: String::default(),
+            export_animation_frame_delay_ms: DEFAULT_FRAME_DURATION_MS,
+        }
+    }
+}
+///The events sent from the File Context Menu, grouped so [draw_gui](draw_gui) won't go over bevy's system parameter limit
+#[derive(SystemParam)]
+pub struct FileEventWriters<'a> {
+    export: EventWriter<'a, ExportTileSetEvent>,
+    export_animation: EventWriter<'a, ExportAnimationEvent>,
+    open_image: EventWriter<'a, OpenImageEvent>,
+    save_project: EventWriter<'a, SaveProjectEvent>,
+    open_project: EventWriter<'a, OpenProjectEvent>,
 }
 ///The Current Selection of the Options Context Menu
 #[derive(PartialEq, Eq, Debug)]
@@ -34,9 +83,15 @@ pub fn draw_gui(
     mut ctx_menu_state: Local<ContextMenuState>,
     mut new_tileset_window_data: Local<TileSetSettings>,
     mut tileset_entities: Local<Vec<Entity>>,
-    mut tileset_entity_names_query: Query<&mut TileSetName>,
+    tileset_entity_names_query: Query<(&TileSetName, &TileSetOrder)>,
+    added_tileset_entities_query: Query<Entity, Added<TileSetName>>,
     selected_tileset_entity_query: Query<&CurrentlySelected>,
     mut added_tilesets: Local<u32>,
+    mut tileset_order_counter: ResMut<TileSetOrderCounter>,
+    mut file_windows_data: Local<FileWindowsData>,
+    mut file_events: FileEventWriters,
+    project_path: Res<ProjectPath>,
+    mut grid_settings: ResMut<GridSettings>,
     egui_context: ResMut<EguiContext>,
     input: Res<Input<KeyCode>>,
 ) {
@@ -44,6 +99,16 @@ pub fn draw_gui(
     if input.pressed(KeyCode::Escape) {
         *ctx_menu_state = ContextMenuState::None;
     }
+    //Tilesets can be spawned by the gui or when loading files, either way they get a tab
+    tileset_entities.retain(|entity| tileset_entity_names_query.get(*entity).is_ok());
+    tileset_entities.extend(added_tileset_entities_query.iter());
+    //Tilesets spawned in the same frame are queried in no particular order
+    tileset_entities.sort_by_key(|entity| {
+        tileset_entity_names_query
+            .get(*entity)
+            .map(|(_, order)| *order)
+            .ok()
+    });
     let ctx = egui_context.ctx();
     //The Menu
     egui::TopPanel::top("my_top_panel").show(ctx, |ui| {
@@ -65,21 +130,16 @@ pub fn draw_gui(
                 match &*ctx_menu_state{
                     //If we want to display the File ui, show appropriate ui
                     ContextMenuState::File(selected) => {
+                        let selected = *selected;
                         //If we pressed the new button now or earlier, show a window for that
-                        if *selected == SelectedFileContextMenuItem::New ||  ui.button("New").clicked() {
+                        if selected == SelectedFileContextMenuItem::New ||  ui.button("New").clicked() {
                             //Make sure the window doesn't disappear on the next update
                             *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::New);
                             //Showing the window itself
                             egui::Window::new("New Tileset").show(ctx, |ui|{
                                 ui.vertical(|ui|{
                                     //Tile settings
-                                    ui.label("Tile:");
-                                    ui.horizontal(|ui|{
-                                        ui.label("Width:").on_hover_text("A single tile's width");
-                                        ui.add(egui::DragValue::new(&mut new_tileset_window_data.tile_width).clamp_range(1_f32..=u16::MAX as f32));
-                                        ui.label("Height:").on_hover_text("A single tile's height");
-                                        ui.add(egui::DragValue::new(&mut new_tileset_window_data.tile_height).clamp_range(1_f32..=u16::MAX as f32));
-                                    });
+                                    tile_size_ui(ui, &mut new_tileset_window_data);
                                     //Tileset settings
                                     ui.label("TileSet:");
                                     ui.horizontal(|ui|{
@@ -91,8 +151,7 @@ pub fn draw_gui(
                                     //If we confirmed the creation of a new tileset
                                     if ui.button("Create new").clicked(){
                                         //Spawn a TileSet Entity
-                                        let new_tileset_entity = commands.spawn_bundle(TileSetBundle::new(*new_tileset_window_data,*added_tilesets + 1)).id();
-                                        tileset_entities.push(new_tileset_entity);
+                                        commands.spawn_bundle(TileSetBundle::new(*new_tileset_window_data,*added_tilesets + 1, tileset_order_counter.next()));
                                         *new_tileset_window_data = TileSetSettings::default();
                                         *ctx_menu_state = ContextMenuState::None;
                                         *added_tilesets += 1;
@@ -100,10 +159,141 @@ pub fn draw_gui(
                                 });
                             });
                         }
+                        //If we pressed the open button now or earlier, show a window for that
+                        if selected == SelectedFileContextMenuItem::Open || ui.button("Open").clicked() {
+                            *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::Open);
+                            egui::Window::new("Open Project").show(ctx, |ui|{
+                                ui.vertical(|ui|{
+                                    ui.horizontal(|ui|{
+                                        ui.label("Path:").on_hover_text("The project file to open, the open tilesets will be closed");
+                                        ui.text_edit_singleline(&mut file_windows_data.project_path);
+                                    });
+                                    if ui.button("Open").clicked() && !file_windows_data.project_path.is_empty() {
+                                        file_events.open_project.send(OpenProjectEvent {
+                                            path: PathBuf::from(&file_windows_data.project_path),
+                                        });
+                                        *ctx_menu_state = ContextMenuState::None;
+                                    }
+                                });
+                            });
+                        }
+                        //If we pressed the open image button now or earlier, show a window for that
+                        if selected == SelectedFileContextMenuItem::OpenImage || ui.button("Open Image").clicked() {
+                            *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::OpenImage);
+                            egui::Window::new("Open Image").show(ctx, |ui|{
+                                ui.vertical(|ui|{
+                                    ui.horizontal(|ui|{
+                                        ui.label("Path:").on_hover_text("The png sprite sheet to edit");
+                                        ui.text_edit_singleline(&mut file_windows_data.open_image_path);
+                                    });
+                                    //The tileset's size comes from the image, we only need to know how to slice it
+                                    tile_size_ui(ui, &mut file_windows_data.open_image_settings);
+                                    if ui.button("Open").clicked() && !file_windows_data.open_image_path.is_empty() {
+                                        file_events.open_image.send(OpenImageEvent {
+                                            path: PathBuf::from(&file_windows_data.open_image_path),
+                                            tile_width: file_windows_data.open_image_settings.tile_width,
+                                            tile_height: file_windows_data.open_image_settings.tile_height,
+                                        });
+                                        *ctx_menu_state = ContextMenuState::None;
+                                    }
+                                });
+                            });
+                        }
+                        //Saving and exporting only make sense when there's a tileset to save
+                        let has_selected_tileset = tileset_entities.iter().any(|entity| selected_tileset_entity_query.get(*entity).is_ok());
+                        //Saving to the last project file doesn't need a window, unless there's no such file yet
+                        if has_selected_tileset && ui.button("Save").clicked() {
+                            if let Some(path) = &project_path.path {
+                                file_events.save_project.send(SaveProjectEvent { path: path.clone() });
+                                *ctx_menu_state = ContextMenuState::None;
+                            } else {
+                                *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::SaveAs);
+                            }
+                        }
+                        if has_selected_tileset && (*ctx_menu_state == ContextMenuState::File(SelectedFileContextMenuItem::SaveAs) || ui.button("Save As").clicked()) {
+                            *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::SaveAs);
+                            egui::Window::new("Save Project").show(ctx, |ui|{
+                                ui.vertical(|ui|{
+                                    ui.horizontal(|ui|{
+                                        ui.label("Path:").on_hover_text("Where the project file will be written");
+                                        ui.text_edit_singleline(&mut file_windows_data.project_path);
+                                    });
+                                    if ui.button("Save").clicked() && !file_windows_data.project_path.is_empty() {
+                                        file_events.save_project.send(SaveProjectEvent {
+                                            path: PathBuf::from(&file_windows_data.project_path),
+                                        });
+                                        *ctx_menu_state = ContextMenuState::None;
+                                    }
+                                });
+                            });
+                        }
+                        if has_selected_tileset && (selected == SelectedFileContextMenuItem::Export || ui.button("Export").clicked()) {
+                            *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::Export);
+                            egui::Window::new("Export Tileset").show(ctx, |ui|{
+                                ui.vertical(|ui|{
+                                    ui.horizontal(|ui|{
+                                        ui.label("Path:").on_hover_text("Where the png sprite sheet will be written");
+                                        ui.text_edit_singleline(&mut file_windows_data.export_path);
+                                    });
+                                    if ui.button("Export").clicked() && !file_windows_data.export_path.is_empty() {
+                                        file_events.export.send(ExportTileSetEvent {
+                                            path: PathBuf::from(&file_windows_data.export_path),
+                                        });
+                                        *ctx_menu_state = ContextMenuState::None;
+                                    }
+                                });
+                            });
+                        }
+                        if has_selected_tileset && (selected == SelectedFileContextMenuItem::ExportAnimation || ui.button("Export Animation").clicked()) {
+                            *ctx_menu_state = ContextMenuState::File(SelectedFileContextMenuItem::ExportAnimation);
+                            egui::Window::new("Export Animation").show(ctx, |ui|{
+                                ui.vertical(|ui|{
+                                    ui.horizontal(|ui|{
+                                        ui.label("Path:").on_hover_text("Where the animation will be written, a .gif or an animated .png");
+                                        ui.text_edit_singleline(&mut file_windows_data.export_animation_path);
+                                    });
+                                    ui.horizontal(|ui|{
+                                        ui.label("Tiles:").on_hover_text("The frames' tiles in order, written as x,y and separated by spaces, the bottom left tile is 0,0");
+                                        ui.text_edit_singleline(&mut file_windows_data.export_animation_tiles);
+                                    });
+                                    ui.horizontal(|ui|{
+                                        ui.label("Frame delay (ms):");
+                                        ui.add(egui::DragValue::new(&mut file_windows_data.export_animation_frame_delay_ms).clamp_range(1..=10000));
+                                    });
+                                    let tiles = parse_tile_positions(&file_windows_data.export_animation_tiles);
+                                    if let Err(error) = &tiles {
+                                        ui.label(error.to_string());
+                                    }
+                                    if ui.button("Export").clicked() && !file_windows_data.export_animation_path.is_empty() {
+                                        if let Ok(tiles) = tiles {
+                                            if !tiles.is_empty() {
+                                                file_events.export_animation.send(ExportAnimationEvent {
+                                                    path: PathBuf::from(&file_windows_data.export_animation_path),
+                                                    tiles,
+                                                    frame_delay_ms: file_windows_data.export_animation_frame_delay_ms,
+                                                });
+                                                *ctx_menu_state = ContextMenuState::None;
+                                            }
+                                        }
+                                    }
+                                });
+                            });
+                        }
                     }
                     //If we want to display the Options ui, show appropriate ui
                     ContextMenuState::Options(_selected) => {
-
+                        //Editing a copy so the grids are only updated when the settings actually change
+                        let mut new_grid_settings = grid_settings.clone();
+                        ui.label("Grid:");
+                        ui.checkbox(&mut new_grid_settings.show_tile_grid, "Tile grid").on_hover_text("Draw the borders between the tiles");
+                        rgba_ui(ui, &mut new_grid_settings.tile_grid_color);
+                        ui.checkbox(&mut new_grid_settings.show_pixel_grid, "Pixel grid").on_hover_text("Draw the borders between the pixels when zoomed in");
+                        rgba_ui(ui, &mut new_grid_settings.pixel_grid_color);
+                        ui.add(egui::Slider::new(&mut new_grid_settings.pixel_grid_min_zoom, 2.0..=32.0).text("Pixel grid zoom"))
+                            .on_hover_text("How many pixels on the screen a tileset pixel needs to cover before the pixel grid shows");
+                        if new_grid_settings != *grid_settings {
+                            *grid_settings = new_grid_settings;
+                        }
                     }
                     //This can't happen since it's checked above but rust is weird
                     ContextMenuState::None => {}
@@ -119,7 +309,7 @@ pub fn draw_gui(
         egui::TopPanel::top("tabs").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 for entity in tileset_entities.iter() {
-                    if let Ok(mut tileset_name) = tileset_entity_names_query.get_mut(*entity) {
+                    if let Ok((tileset_name, _)) = tileset_entity_names_query.get(*entity) {
                         if ui
                             .selectable_label(
                                 selected_tileset_entity_query.get(*entity).is_ok(),
@@ -136,3 +326,19 @@ pub fn draw_gui(
         });
     }
 }
+///Draws the drag values for a single tile's size in a [TileSetSettings](TileSetSettings)
+fn tile_size_ui(ui: &mut egui::Ui, tileset_settings: &mut TileSetSettings) {
+    ui.label("Tile:");
+    ui.horizontal(|ui| {
+        ui.label("Width:").on_hover_text("A single tile's width");
+        ui.add(
+            egui::DragValue::new(&mut tileset_settings.tile_width)
+                .clamp_range(1_f32..=u16::MAX as f32),
+        );
+        ui.label("Height:").on_hover_text("A single tile's height");
+        ui.add(
+            egui::DragValue::new(&mut tileset_settings.tile_height)
+                .clamp_range(1_f32..=u16::MAX as f32),
+        );
+    });
+}
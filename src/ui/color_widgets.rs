@@ -0,0 +1,132 @@
+use bevy_egui::egui;
+
+///Draws a preview of an RGBA8 color that can be clicked
+pub fn color_swatch(ui: &mut egui::Ui, color: [u8; 4], selected: bool) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::click());
+    ui.painter().rect_filled(
+        rect,
+        2.0,
+        egui::Color32::from_rgba_unmultiplied(color[0], color[1], color[2], color[3]),
+    );
+    if selected {
+        ui.painter()
+            .rect_stroke(rect, 2.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+    }
+    response
+}
+///Lets the user edit each channel of an RGBA8 color
+pub fn rgba_ui(ui: &mut egui::Ui, color: &mut [u8; 4]) {
+    ui.horizontal(|ui| {
+        for (channel, name) in color.iter_mut().zip(["R", "G", "B", "A"].iter()) {
+            ui.label(*name);
+            ui.add(egui::DragValue::new(channel).clamp_range(0..=u8::MAX));
+        }
+    });
+}
+///Lets the user edit the hue, saturation and value of an RGBA8 color
+pub fn hsv_ui(ui: &mut egui::Ui, color: &mut [u8; 4]) {
+    let [mut hue, mut saturation, mut value] = rgb_to_hsv([color[0], color[1], color[2]]);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("H");
+        changed |= ui
+            .add(egui::DragValue::new(&mut hue).clamp_range(0.0..=360.0))
+            .changed();
+        ui.label("S");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut saturation)
+                    .clamp_range(0.0..=1.0)
+                    .speed(0.01),
+            )
+            .changed();
+        ui.label("V");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut value)
+                    .clamp_range(0.0..=1.0)
+                    .speed(0.01),
+            )
+            .changed();
+    });
+    //Converting back only when needed, so the color doesn't drift from rounding
+    if changed {
+        let [red, green, blue] = hsv_to_rgb([hue, saturation, value]);
+        *color = [red, green, blue, color[3]];
+    }
+}
+///Lets the user type a color as hex, like #ff8800 or #ff8800cc
+///
+///The text is kept between frames by the caller, it's only applied when it's a valid color
+pub fn hex_ui(ui: &mut egui::Ui, color: &mut [u8; 4], hex_text: &mut String) {
+    ui.horizontal(|ui| {
+        ui.label("Hex");
+        let response = ui.text_edit_singleline(hex_text);
+        if response.changed() {
+            if let Some(parsed_color) = parse_hex_color(hex_text) {
+                *color = parsed_color;
+            }
+        } else if !response.has_focus() {
+            *hex_text = to_hex_color(*color);
+        }
+    });
+}
+///Parses a color written as RRGGBB or RRGGBBAA hex, with or without a #
+pub fn parse_hex_color(text: &str) -> Option<[u8; 4]> {
+    let text = text.trim().trim_start_matches('#');
+    if (text.len() != 6 && text.len() != 8) || !text.is_ascii() {
+        return None;
+    }
+    let mut color = [0, 0, 0, u8::MAX];
+    for (channel_index, channel) in color.iter_mut().enumerate().take(text.len() / 2) {
+        *channel = u8::from_str_radix(&text[channel_index * 2..channel_index * 2 + 2], 16).ok()?;
+    }
+    Some(color)
+}
+///Writes a color as #RRGGBBAA hex
+pub fn to_hex_color(color: [u8; 4]) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color[0], color[1], color[2], color[3]
+    )
+}
+///Converts an RGB8 color to hue in degrees, saturation and value from 0 to 1
+pub fn rgb_to_hsv(rgb: [u8; 3]) -> [f32; 3] {
+    let [red, green, blue] = [
+        rgb[0] as f32 / 255.0,
+        rgb[1] as f32 / 255.0,
+        rgb[2] as f32 / 255.0,
+    ];
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == red {
+        60.0 * ((green - blue) / delta).rem_euclid(6.0)
+    } else if max == green {
+        60.0 * ((blue - red) / delta + 2.0)
+    } else {
+        60.0 * ((red - green) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation, max]
+}
+///Converts hue in degrees, saturation and value from 0 to 1 to an RGB8 color
+pub fn hsv_to_rgb(hsv: [f32; 3]) -> [u8; 3] {
+    let [hue, saturation, value] = hsv;
+    let chroma = value * saturation;
+    let hue_section = (hue / 60.0).rem_euclid(6.0);
+    let x = chroma * (1.0 - (hue_section % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue_section as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let offset = value - chroma;
+    let to_u8 = |channel: f32| ((channel + offset) * 255.0).round() as u8;
+    [to_u8(red), to_u8(green), to_u8(blue)]
+}
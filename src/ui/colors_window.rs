@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use super::color_widgets::{color_swatch, hex_ui, hsv_ui, rgba_ui};
+use crate::data::resources::DrawingColors;
+///Which color is being edited and the hex text the user typed, kept between frames
+#[derive(Debug, Default)]
+pub struct ColorsWindowData {
+    editing_secondary: bool,
+    hex_text: String,
+}
+///Drawing the window for editing the primary and secondary colors
+pub fn draw_colors_window(
+    mut drawing_colors: ResMut<DrawingColors>,
+    mut colors_window_data: Local<ColorsWindowData>,
+    egui_context: ResMut<EguiContext>,
+) {
+    let ctx = egui_context.ctx();
+    //Editing a copy so the tools only see the colors change when they actually do
+    let mut new_drawing_colors = drawing_colors.clone();
+    egui::Window::new("Colors").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if color_swatch(
+                ui,
+                new_drawing_colors.primary,
+                !colors_window_data.editing_secondary,
+            )
+            .on_hover_text("Primary, drawn with the left mouse button")
+            .clicked()
+            {
+                colors_window_data.editing_secondary = false;
+            }
+            if color_swatch(
+                ui,
+                new_drawing_colors.secondary,
+                colors_window_data.editing_secondary,
+            )
+            .on_hover_text("Secondary, drawn with the right mouse button")
+            .clicked()
+            {
+                colors_window_data.editing_secondary = true;
+            }
+            if ui.button("Swap").clicked() {
+                std::mem::swap(
+                    &mut new_drawing_colors.primary,
+                    &mut new_drawing_colors.secondary,
+                );
+            }
+        });
+        let ColorsWindowData {
+            editing_secondary,
+            hex_text,
+        } = &mut *colors_window_data;
+        let color = if *editing_secondary {
+            &mut new_drawing_colors.secondary
+        } else {
+            &mut new_drawing_colors.primary
+        };
+        rgba_ui(ui, color);
+        hsv_ui(ui, color);
+        hex_ui(ui, color, hex_text);
+    });
+    if new_drawing_colors != *drawing_colors {
+        *drawing_colors = new_drawing_colors;
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::data::{
+    events::HistoryEvent, history::TileSetHistory, shared_components::CurrentlySelected,
+};
+///Drawing the window that lists the strokes of the currently selected tileset, clicking a stroke goes back or forward to it
+pub fn draw_history_window(
+    tileset_query: Query<&TileSetHistory, With<CurrentlySelected>>,
+    mut history_events: EventWriter<HistoryEvent>,
+    egui_context: ResMut<EguiContext>,
+) {
+    if let Ok(history) = tileset_query.single() {
+        let ctx = egui_context.ctx();
+        egui::Window::new("History").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Undo").on_hover_text("Ctrl+Z").clicked() {
+                    history_events.send(HistoryEvent::Undo);
+                }
+                if ui.button("Redo").on_hover_text("Ctrl+Shift+Z").clicked() {
+                    history_events.send(HistoryEvent::Redo);
+                }
+            });
+            egui::ScrollArea::auto_sized().show(ui, |ui| {
+                let undo_count = history.undo_stack.len();
+                //The oldest stroke is at the top, the selected one is the last stroke that wasn't undone
+                if ui.selectable_label(undo_count == 0, "Start").clicked() {
+                    for _ in 0..undo_count {
+                        history_events.send(HistoryEvent::Undo);
+                    }
+                }
+                for (index, entry) in history.undo_stack.iter().enumerate() {
+                    if ui
+                        .selectable_label(index + 1 == undo_count, &entry.name)
+                        .clicked()
+                    {
+                        for _ in index + 1..undo_count {
+                            history_events.send(HistoryEvent::Undo);
+                        }
+                    }
+                }
+                //Undone strokes come after it, the next one to be redone is the last in the redo stack
+                for (index, entry) in history.redo_stack.iter().rev().enumerate() {
+                    if ui
+                        .selectable_label(false, format!("{} (undone)", entry.name))
+                        .clicked()
+                    {
+                        for _ in 0..=index {
+                            history_events.send(HistoryEvent::Redo);
+                        }
+                    }
+                }
+            });
+        });
+    }
+}
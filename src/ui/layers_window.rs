@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::data::{
+    events::LayerEvent,
+    layers::{BlendMode, TileSetLayers},
+    shared_components::CurrentlySelected,
+};
+///Drawing the window with the layers of the currently selected tileset, the top layer is shown first
+pub fn draw_layers_window(
+    mut tileset_query: Query<&mut TileSetLayers, With<CurrentlySelected>>,
+    mut layer_events: EventWriter<LayerEvent>,
+    egui_context: ResMut<EguiContext>,
+) {
+    if let Ok(mut tileset_layers) = tileset_query.single_mut() {
+        //Editing a copy, so the layers are only marked as changed when something changed and the tiles aren't redrawn every frame
+        let mut layers = tileset_layers.layers.clone();
+        let active_layer = tileset_layers.active_layer;
+        let ctx = egui_context.ctx();
+        egui::Window::new("Layers").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Add").clicked() {
+                    layer_events.send(LayerEvent::Add);
+                }
+                if layers.len() > 1 && ui.button("Remove").clicked() {
+                    layer_events.send(LayerEvent::Remove(active_layer));
+                }
+            });
+            ui.separator();
+            for (index, layer) in layers.iter_mut().enumerate().rev() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut layer.visible, "").on_hover_text("Visible");
+                    ui.checkbox(&mut layer.locked, "").on_hover_text("Locked");
+                    if ui
+                        .selectable_label(index == active_layer, layer.name.as_str())
+                        .clicked()
+                    {
+                        layer_events.send(LayerEvent::Select(index));
+                    }
+                });
+            }
+            if let Some(layer) = layers.get_mut(active_layer) {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut layer.name);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Opacity:");
+                    ui.add(egui::Slider::new(&mut layer.opacity, 0..=u8::MAX));
+                });
+                ui.horizontal(|ui| {
+                    for blend_mode in BlendMode::ALL.iter() {
+                        ui.selectable_value(&mut layer.blend_mode, *blend_mode, blend_mode.name());
+                    }
+                });
+            }
+        });
+        if layers != tileset_layers.layers {
+            tileset_layers.layers = layers;
+        }
+    }
+}
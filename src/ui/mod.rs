@@ -1,2 +1,10 @@
+pub mod animation_preview_window;
 pub mod bevy_egui;
+pub mod color_widgets;
+pub mod colors_window;
+pub mod history_window;
+pub mod layers_window;
+pub mod palette_window;
+pub mod timeline_window;
+pub mod tools_window;
 //pub mod bevy_ui;
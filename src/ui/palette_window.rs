@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use super::color_widgets::color_swatch;
+use crate::data::{
+    events::{ColorModeEvent, OpenPaletteEvent, RemapPaletteIndicesEvent, SavePaletteEvent},
+    palette::{Palette, Swatch},
+    resources::DrawingColors,
+    shared_components::CurrentlySelected,
+    tileset_entity::{TileSetPalette, TileSetSettings},
+};
+///The palette file path the user typed and the selected swatch, kept between frames
+#[derive(Debug, Default)]
+pub struct PaletteWindowData {
+    path: String,
+    selected_swatch: Option<usize>,
+}
+///Drawing the window with the palette's swatches, clicking a swatch makes it the primary color and right clicking makes it the secondary one
+pub fn draw_palette_window(
+    mut palette: ResMut<Palette>,
+    mut drawing_colors: ResMut<DrawingColors>,
+    mut palette_window_data: Local<PaletteWindowData>,
+    mut open_palette_events: EventWriter<OpenPaletteEvent>,
+    mut save_palette_events: EventWriter<SavePaletteEvent>,
+    mut color_mode_events: EventWriter<ColorModeEvent>,
+    mut remap_events: EventWriter<RemapPaletteIndicesEvent>,
+    selected_tileset_query: Query<
+        Option<&TileSetPalette>,
+        (With<TileSetSettings>, With<CurrentlySelected>),
+    >,
+    egui_context: ResMut<EguiContext>,
+) {
+    //Opening another palette can remove the selected swatch
+    if let Some(selected_swatch) = palette_window_data.selected_swatch {
+        if selected_swatch >= palette.swatches.len() {
+            palette_window_data.selected_swatch = None;
+        }
+    }
+    let ctx = egui_context.ctx();
+    //Editing a copy, so the palette is only marked as changed when something changed and indexed tiles aren't recolored every frame
+    let mut new_palette = palette.clone();
+    egui::Window::new("Palette").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut palette_window_data.path)
+                .on_hover_text("A .gpl, .hex, .pal or .act file");
+            if ui.button("Open").clicked() && !palette_window_data.path.is_empty() {
+                open_palette_events.send(OpenPaletteEvent {
+                    path: palette_window_data.path.clone().into(),
+                });
+            }
+            if ui.button("Save").clicked() && !palette_window_data.path.is_empty() {
+                save_palette_events.send(SavePaletteEvent {
+                    path: palette_window_data.path.clone().into(),
+                });
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut new_palette.name);
+        });
+        //Indexed tilesets are recolored when the palette is edited
+        if let Ok(tileset_palette) = selected_tileset_query.single() {
+            ui.horizontal(|ui| {
+                if tileset_palette.is_some() {
+                    ui.label("The selected tileset is indexed");
+                    if ui.button("Convert to RGBA").clicked() {
+                        color_mode_events.send(ColorModeEvent::ToRgba);
+                    }
+                } else if ui
+                    .button("Index the selected tileset")
+                    .on_hover_text("Changes every pixel to the nearest color in the palette")
+                    .clicked()
+                {
+                    color_mode_events.send(ColorModeEvent::ToIndexed);
+                }
+            });
+        }
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            for (index, swatch) in new_palette.swatches.iter().enumerate() {
+                let is_selected = palette_window_data.selected_swatch == Some(index);
+                let mut response = color_swatch(ui, swatch.color, is_selected);
+                if !swatch.name.is_empty() {
+                    response = response.on_hover_text(&swatch.name);
+                }
+                if response.clicked() {
+                    drawing_colors.primary = swatch.color;
+                    palette_window_data.selected_swatch = Some(index);
+                }
+                if response.secondary_clicked() {
+                    drawing_colors.secondary = swatch.color;
+                    palette_window_data.selected_swatch = Some(index);
+                }
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui
+                .button("Add")
+                .on_hover_text("Adds the primary color")
+                .clicked()
+            {
+                new_palette.swatches.push(Swatch {
+                    color: drawing_colors.primary,
+                    name: String::new(),
+                });
+                palette_window_data.selected_swatch = Some(new_palette.swatches.len() - 1);
+            }
+            if let Some(selected_swatch) = palette_window_data.selected_swatch {
+                //Indexed tiles keep their colors when their swatches move
+                if ui.button("Remove").clicked() {
+                    remap_events.send(RemapPaletteIndicesEvent {
+                        new_indices: new_palette.remove_swatch(selected_swatch),
+                    });
+                    palette_window_data.selected_swatch = None;
+                    return;
+                }
+                if ui.button("Move left").clicked() && selected_swatch > 0 {
+                    remap_events.send(RemapPaletteIndicesEvent {
+                        new_indices: new_palette
+                            .swap_swatches(selected_swatch, selected_swatch - 1),
+                    });
+                    palette_window_data.selected_swatch = Some(selected_swatch - 1);
+                }
+                if ui.button("Move right").clicked()
+                    && selected_swatch + 1 < new_palette.swatches.len()
+                {
+                    remap_events.send(RemapPaletteIndicesEvent {
+                        new_indices: new_palette
+                            .swap_swatches(selected_swatch, selected_swatch + 1),
+                    });
+                    palette_window_data.selected_swatch = Some(selected_swatch + 1);
+                }
+                if ui
+                    .button("Set")
+                    .on_hover_text("Replaces the swatch's color with the primary color")
+                    .clicked()
+                {
+                    new_palette.swatches[selected_swatch].color = drawing_colors.primary;
+                }
+            }
+        });
+        if let Some(selected_swatch) = palette_window_data.selected_swatch {
+            ui.horizontal(|ui| {
+                ui.label("Swatch name:");
+                ui.text_edit_singleline(&mut new_palette.swatches[selected_swatch].name);
+            });
+        }
+    });
+    if new_palette != *palette {
+        *palette = new_palette;
+    }
+}
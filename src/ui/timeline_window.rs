@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use super::color_widgets::rgba_ui;
+use crate::data::{
+    animations::{
+        Animation, AnimationFrame, AnimationPlayback, OnionSkinSettings, TileSetAnimations,
+        DEFAULT_FRAME_DURATION_MS,
+    },
+    shared_components::CurrentlySelected,
+    tileset_entity::TileSetSettings,
+};
+///The selected frame and the tile the user wants to add, kept between frames
+#[derive(Debug, Default)]
+pub struct TimelineWindowData {
+    selected_frame: Option<usize>,
+    new_frame_position: [u32; 2],
+}
+///Drawing the window for making the selected tileset's animations and playing them in the preview
+pub fn draw_timeline_window(
+    mut tileset_query: Query<(&mut TileSetAnimations, &TileSetSettings), With<CurrentlySelected>>,
+    mut playback: ResMut<AnimationPlayback>,
+    mut onion_skin_settings: ResMut<OnionSkinSettings>,
+    mut timeline_window_data: Local<TimelineWindowData>,
+    egui_context: ResMut<EguiContext>,
+) {
+    let (mut tileset_animations, tileset_settings) = match tileset_query.single_mut() {
+        Ok(tileset) => tileset,
+        Err(_) => return,
+    };
+    //Selecting another tileset can leave the previewed animation without an animation
+    if let Some(animation_index) = playback.animation {
+        if animation_index >= tileset_animations.animations.len() {
+            playback.animation = None;
+            timeline_window_data.selected_frame = None;
+        }
+    }
+    let ctx = egui_context.ctx();
+    egui::Window::new("Timeline").show(ctx, |ui| {
+        //Editing a copy so the onion skin is only rebuilt when the settings actually change
+        let mut new_onion_skin_settings = onion_skin_settings.clone();
+        egui::CollapsingHeader::new("Onion skin").show(ui, |ui| {
+            ui.checkbox(&mut new_onion_skin_settings.enabled, "Enabled")
+                .on_hover_text("Shows the tiles before and after the hovered tile under it");
+            ui.horizontal(|ui| {
+                ui.label("Frames:");
+                ui.add(
+                    egui::DragValue::new(&mut new_onion_skin_settings.frames).clamp_range(1..=8),
+                );
+            });
+            ui.label("Before:");
+            rgba_ui(ui, &mut new_onion_skin_settings.previous_tint);
+            ui.label("After:");
+            rgba_ui(ui, &mut new_onion_skin_settings.next_tint);
+            ui.add(
+                egui::Slider::new(&mut new_onion_skin_settings.opacity, 0..=u8::MAX)
+                    .text("Opacity"),
+            );
+        });
+        if new_onion_skin_settings != *onion_skin_settings {
+            *onion_skin_settings = new_onion_skin_settings;
+        }
+        ui.horizontal_wrapped(|ui| {
+            for (index, animation) in tileset_animations.animations.iter().enumerate() {
+                if ui
+                    .selectable_label(playback.animation == Some(index), animation.name.as_str())
+                    .clicked()
+                {
+                    playback.animation = Some(index);
+                    playback.rewind();
+                    timeline_window_data.selected_frame = None;
+                }
+            }
+            if ui.button("New").clicked() {
+                let name = format!("Animation {}", tileset_animations.animations.len() + 1);
+                tileset_animations.animations.push(Animation {
+                    name,
+                    frames: Vec::new(),
+                });
+                playback.animation = Some(tileset_animations.animations.len() - 1);
+                playback.rewind();
+                timeline_window_data.selected_frame = None;
+            }
+        });
+        let animation_index = match playback.animation {
+            Some(animation_index) => animation_index,
+            None => return,
+        };
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut tileset_animations.animations[animation_index].name);
+            if ui.button("Remove").clicked() {
+                tileset_animations.animations.remove(animation_index);
+                playback.animation = None;
+                playback.playing = false;
+                timeline_window_data.selected_frame = None;
+            }
+        });
+        //The animation could have been removed
+        let animation = match tileset_animations.animations.get_mut(animation_index) {
+            Some(animation) => animation,
+            None => return,
+        };
+        ui.horizontal(|ui| {
+            let play_text = if playback.playing { "Pause" } else { "Play" };
+            if ui.button(play_text).clicked() {
+                playback.playing = !playback.playing;
+            }
+            if ui.button("Stop").clicked() {
+                playback.playing = false;
+                playback.rewind();
+            }
+        });
+        //The frames are shown as the positions of their tiles, the one that's previewed is marked
+        ui.horizontal_wrapped(|ui| {
+            for (index, frame) in animation.frames.iter().enumerate() {
+                let marker = if index == playback.frame { ">" } else { "" };
+                let text = format!(
+                    "{}{},{}\n{}ms",
+                    marker, frame.tile_position.x, frame.tile_position.y, frame.duration_ms
+                );
+                if ui
+                    .selectable_label(timeline_window_data.selected_frame == Some(index), text)
+                    .clicked()
+                {
+                    timeline_window_data.selected_frame = Some(index);
+                }
+            }
+        });
+        let max_tile_position = [
+            tileset_settings.tileset_width as u32 - 1,
+            tileset_settings.tileset_height as u32 - 1,
+        ];
+        if let Some(selected_frame) = timeline_window_data.selected_frame {
+            if selected_frame < animation.frames.len() {
+                ui.separator();
+                let frame = &mut animation.frames[selected_frame];
+                ui.horizontal(|ui| {
+                    ui.label("Duration (ms):");
+                    ui.add(egui::DragValue::new(&mut frame.duration_ms).clamp_range(1..=10000));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Tile:");
+                    ui.add(
+                        egui::DragValue::new(&mut frame.tile_position.x)
+                            .clamp_range(0..=max_tile_position[0]),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut frame.tile_position.y)
+                            .clamp_range(0..=max_tile_position[1]),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Move left").clicked() && selected_frame > 0 {
+                        animation.frames.swap(selected_frame, selected_frame - 1);
+                        timeline_window_data.selected_frame = Some(selected_frame - 1);
+                    }
+                    if ui.button("Move right").clicked()
+                        && selected_frame + 1 < animation.frames.len()
+                    {
+                        animation.frames.swap(selected_frame, selected_frame + 1);
+                        timeline_window_data.selected_frame = Some(selected_frame + 1);
+                    }
+                    if ui.button("Remove frame").clicked() {
+                        animation.frames.remove(selected_frame);
+                        timeline_window_data.selected_frame = None;
+                    }
+                });
+            }
+        }
+        ui.separator();
+        //Character sheets usually have an animation per row of tiles
+        ui.horizontal(|ui| {
+            let new_frame_position = &mut timeline_window_data.new_frame_position;
+            ui.label("Tile:");
+            ui.add(
+                egui::DragValue::new(&mut new_frame_position[0])
+                    .clamp_range(0..=max_tile_position[0]),
+            );
+            ui.add(
+                egui::DragValue::new(&mut new_frame_position[1])
+                    .clamp_range(0..=max_tile_position[1]),
+            );
+            let [x, y] = *new_frame_position;
+            if ui.button("Add frame").clicked() {
+                animation.frames.push(AnimationFrame {
+                    tile_position: UVec2::new(x, y),
+                    duration_ms: DEFAULT_FRAME_DURATION_MS,
+                });
+            }
+            if ui
+                .button("Add row")
+                .on_hover_text("Adds every tile in the tile's row, from left to right")
+                .clicked()
+            {
+                for x in 0..=max_tile_position[0] {
+                    animation.frames.push(AnimationFrame {
+                        tile_position: UVec2::new(x, y),
+                        duration_ms: DEFAULT_FRAME_DURATION_MS,
+                    });
+                }
+            }
+        });
+    });
+}
@@ -0,0 +1,213 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::{egui, EguiContext};
+
+use crate::data::{
+    assets::Pattern,
+    resources::{BrushMode, BrushSettings, FillMode, FillSettings, SelectedTool, ShapeSettings},
+    selection::{MaskOperation, MaskSettings},
+};
+///The text the user typed in the tools window and the last pattern used, kept between frames
+#[derive(Debug, Default)]
+pub struct ToolsWindowData {
+    pattern_path: String,
+    ///The pattern the pencil goes back to after using other tools
+    pattern_handle: Option<Handle<Pattern>>,
+}
+///Drawing the window for choosing the current tool and it's settings
+pub fn draw_tools_window(
+    mut tool: ResMut<SelectedTool>,
+    mut brush_settings: ResMut<BrushSettings>,
+    mut fill_settings: ResMut<FillSettings>,
+    mut shape_settings: ResMut<ShapeSettings>,
+    mut mask_settings: ResMut<MaskSettings>,
+    mut tools_window_data: Local<ToolsWindowData>,
+    asset_server: Res<AssetServer>,
+    egui_context: ResMut<EguiContext>,
+) {
+    //Remember the pattern the brushes started with
+    if let Some(pattern_handle) = tool.pattern_handle() {
+        tools_window_data.pattern_handle = Some(pattern_handle.clone());
+    }
+    let ctx = egui_context.ctx();
+    egui::Window::new("Tools").show(ctx, |ui| {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Pan), "Pan")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Pan;
+                }
+                if let Some(pattern_handle) = &tools_window_data.pattern_handle {
+                    if ui
+                        .selectable_label(matches!(*tool, SelectedTool::Pencil { .. }), "Pencil")
+                        .clicked()
+                    {
+                        *tool = SelectedTool::Pencil {
+                            pattern_handle: pattern_handle.clone(),
+                        };
+                    }
+                    if ui
+                        .selectable_label(matches!(*tool, SelectedTool::Eraser { .. }), "Eraser")
+                        .clicked()
+                    {
+                        *tool = SelectedTool::Eraser {
+                            pattern_handle: pattern_handle.clone(),
+                        };
+                    }
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Fill), "Fill")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Fill;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Picker), "Picker")
+                    .on_hover_text("Alt click with the pencil to pick without switching tools")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Picker;
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Line), "Line")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Line;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Rectangle), "Rectangle")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Rectangle;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Ellipse), "Ellipse")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Ellipse;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Select), "Select")
+                    .on_hover_text(
+                        "Drag to select, drag the selection to move it, Enter to put it down",
+                    )
+                    .clicked()
+                {
+                    *tool = SelectedTool::Select;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::Lasso), "Lasso")
+                    .on_hover_text("Drag around the pixels the brush can paint, Ctrl+D to clear")
+                    .clicked()
+                {
+                    *tool = SelectedTool::Lasso;
+                }
+                if ui
+                    .selectable_label(matches!(*tool, SelectedTool::MagicWand), "Magic Wand")
+                    .on_hover_text(
+                        "Click to select the pixels with the same color, Ctrl+D to clear",
+                    )
+                    .clicked()
+                {
+                    *tool = SelectedTool::MagicWand;
+                }
+            });
+            //Brushes are loaded through the asset server, so they're reloaded when their file changes
+            ui.label("Pattern:");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut tools_window_data.pattern_path)
+                    .on_hover_text("A .pattern or .patternpng file");
+                if ui.button("Load").clicked() && !tools_window_data.pattern_path.is_empty() {
+                    let pattern_handle: Handle<Pattern> =
+                        asset_server.load(tools_window_data.pattern_path.as_str());
+                    tools_window_data.pattern_handle = Some(pattern_handle.clone());
+                    //The eraser keeps erasing, any other tool switches to the pencil
+                    *tool = match *tool {
+                        SelectedTool::Eraser { .. } => SelectedTool::Eraser { pattern_handle },
+                        _ => SelectedTool::Pencil { pattern_handle },
+                    };
+                }
+            });
+            if let Some(pattern_handle) = &tools_window_data.pattern_handle {
+                if asset_server.get_load_state(pattern_handle) == LoadState::Failed {
+                    ui.label("Couldn't load this pattern");
+                }
+            }
+            if let SelectedTool::Pencil { .. } = *tool {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut brush_settings.mode, BrushMode::Mask, "Mask")
+                        .on_hover_text("Paints the pattern's shape with the drawing color");
+                    ui.selectable_value(&mut brush_settings.mode, BrushMode::Stamp, "Stamp")
+                        .on_hover_text("Paints the pattern with it's own colors");
+                });
+            }
+            ui.checkbox(&mut brush_settings.pixel_perfect, "Pixel perfect")
+                .on_hover_text("Removes the doubled pixels in the corners of a stroke");
+            if let SelectedTool::Eraser { .. } = *tool {
+                ui.horizontal(|ui| {
+                    ui.label("Strength:")
+                        .on_hover_text("How much alpha each stamp of the eraser removes");
+                    ui.add(egui::Slider::new(
+                        &mut brush_settings.eraser_strength,
+                        1..=u8::MAX,
+                    ));
+                });
+            }
+            if let SelectedTool::Fill = *tool {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut fill_settings.mode, FillMode::Tile, "Tile")
+                        .on_hover_text("Stops at the clicked tile's borders");
+                    ui.selectable_value(
+                        &mut fill_settings.mode,
+                        FillMode::Contiguous,
+                        "Contiguous",
+                    )
+                    .on_hover_text("Fills the connected pixels across tiles");
+                    ui.selectable_value(&mut fill_settings.mode, FillMode::Global, "Global")
+                        .on_hover_text("Fills every pixel with this color");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Tolerance:")
+                        .on_hover_text("How much a pixel's color can differ and still get filled");
+                    ui.add(egui::Slider::new(&mut fill_settings.tolerance, 0..=u8::MAX));
+                });
+            }
+            if let SelectedTool::Rectangle | SelectedTool::Ellipse = *tool {
+                ui.checkbox(&mut shape_settings.filled, "Filled")
+                    .on_hover_text("Hold shift for squares and circles");
+            }
+            if let SelectedTool::Lasso | SelectedTool::MagicWand = *tool {
+                ui.horizontal(|ui| {
+                    for operation in MaskOperation::ALL.iter() {
+                        ui.selectable_value(
+                            &mut mask_settings.operation,
+                            *operation,
+                            operation.name(),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Hold shift to add, alt to subtract and both to intersect");
+            }
+            if let SelectedTool::MagicWand = *tool {
+                ui.checkbox(&mut mask_settings.wand_contiguous, "Contiguous")
+                    .on_hover_text("Only selects the pixels connected to the clicked pixel");
+                ui.horizontal(|ui| {
+                    ui.label("Tolerance:").on_hover_text(
+                        "How much a pixel's color can differ and still get selected",
+                    );
+                    ui.add(egui::Slider::new(
+                        &mut mask_settings.wand_tolerance,
+                        0..=u8::MAX,
+                    ));
+                });
+            }
+            if let SelectedTool::Line = *tool {
+                ui.label("Hold shift for straight and diagonal lines");
+            }
+        });
+    });
+}